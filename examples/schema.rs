@@ -3,9 +3,13 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use cw20::{BalanceResponse, TokenInfoResponse};
+use cw20::{
+    AllAccountsResponse, AllAllowancesResponse, AllowanceResponse, BalanceResponse,
+    TokenInfoResponse,
+};
 use icov3::msg::{
-    FundraiseInfoResponse, ExecuteMsg, InstantiateMsg, ListResponse, QueryMsg,
+    AllowlistResponse, AttestationPayload, BatchClearingResponse, BidsResponse, ClaimableResponse, CommitmentPayload, CommitmentResponse, ContributionResponse, CurrentPriceResponse, CurveSimulationResponse, FundraiseInfoResponse, ExecuteMsg, InstantiateMsg, ListResponse,
+    MigrateMsg, OracleQueryMsg, PriceResponse, QueryMsg, ReceiveMsg, ReservesResponse, SimulationResponse, VestingStatusResponse,
 };
 
 fn main() {
@@ -17,8 +21,28 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
+    export_schema(&schema_for!(ReceiveMsg), &out_dir);
+    export_schema(&schema_for!(OracleQueryMsg), &out_dir);
+    export_schema(&schema_for!(PriceResponse), &out_dir);
     export_schema(&schema_for!(FundraiseInfoResponse), &out_dir);
     export_schema(&schema_for!(ListResponse), &out_dir);
+    export_schema(&schema_for!(ContributionResponse), &out_dir);
+    export_schema(&schema_for!(SimulationResponse), &out_dir);
+    export_schema(&schema_for!(ClaimableResponse), &out_dir);
+    export_schema(&schema_for!(VestingStatusResponse), &out_dir);
+    export_schema(&schema_for!(ReservesResponse), &out_dir);
+    export_schema(&schema_for!(CurrentPriceResponse), &out_dir);
+    export_schema(&schema_for!(BidsResponse), &out_dir);
+    export_schema(&schema_for!(BatchClearingResponse), &out_dir);
+    export_schema(&schema_for!(CommitmentResponse), &out_dir);
+    export_schema(&schema_for!(CurveSimulationResponse), &out_dir);
+    export_schema(&schema_for!(AllowlistResponse), &out_dir);
+    export_schema(&schema_for!(AttestationPayload), &out_dir);
+    export_schema(&schema_for!(CommitmentPayload), &out_dir);
     export_schema(&schema_for!(BalanceResponse), &out_dir);
     export_schema(&schema_for!(TokenInfoResponse), &out_dir);
+    export_schema(&schema_for!(AllowanceResponse), &out_dir);
+    export_schema(&schema_for!(AllAllowancesResponse), &out_dir);
+    export_schema(&schema_for!(AllAccountsResponse), &out_dir);
 }
@@ -1,32 +1,58 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, to_binary, Binary, Deps, DepsMut, Env, HumanAddr,
-    MessageInfo, Response, StdResult, WasmMsg, Uint128, Decimal
+    attr, coin, from_binary, to_binary, to_vec, Api, BankMsg, Binary, BlockInfo, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut,
+    Env, HumanAddr, MessageInfo, Order, Response, StdError, StdResult, Storage, WasmMsg, Uint128, Decimal
 };
+use cw0::{calc_range_start_human, Expiration, NativeBalance};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::convert::TryInto;
 
 use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::{Bound, U64Key};
 use cw20_base::state::{MinterData, TokenInfo, TOKEN_INFO};
-use cw20_base::contract::{execute_mint, execute_transfer, query_balance, query_token_info};
+use cw20_base::allowances::{
+    execute_burn_from, execute_decrease_allowance, execute_increase_allowance, execute_send_from,
+    execute_transfer_from, query_allowance,
+};
+use cw20_base::contract::{
+    execute_burn, execute_mint, execute_send, execute_transfer, query_balance, query_token_info,
+};
+use cw20_base::enumerable::{query_all_accounts, query_all_allowances};
 
+use crate::curve::BondingCurve;
 use crate::error::ContractError;
+use crate::merkle::{leaf_hash, verify, Hash};
 use crate::msg::{
-    ExecuteMsg, InstantiateMsg, FundraiseInfoResponse, ListResponse, QueryMsg,
+    AllocationProof, AllowlistResponse, Attestation, AttestationPayload, BatchClearingResponse, BidResponse,
+    BidsResponse, ClaimableResponse, CommitPhase, CommitmentPayload, CommitmentResponse, ContributionResponse, CurrentPriceResponse, CurveSimulationResponse, ExecuteMsg, InstantiateMsg, FundraiseInfoResponse, ListResponse,
+    MigrateMsg, OracleQueryMsg, PriceResponse, QueryMsg, ReceiveMsg, ReservesResponse, SaleStatus, SimulationResponse, VestingStatusResponse,
 };
 
-use crate::state::{ICOInfo, Fundraiser, ICO, Rate};
+use crate::state::{
+    clear_batch, cost_of, is_valid_price, reserve_allocations, AllowlistEntry, BatchAuction, Bid, CommitReveal, Commitment,
+    FundraiseAsset, Fundraiser, ICOInfo, Rate, Reserve, TierMode, Totals, ALLOWLIST, BIDS, COMMITMENTS, FUNDRAISERS, ICO,
+    LEGACY_ICO, RESERVES, TOTALS,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:icov3";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
+    msg.validate()?;
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     //flip to be den/num to support multiplication
@@ -41,20 +67,72 @@ pub fn instantiate(
         },
         None => vec![Rate{min: Uint128(0), ratio: base_ratio}],
     };
-    
+
+    let owner = deps.api.canonical_address(&info.sender)?;
+    let treasury = match msg.treasury {
+        Some(treasury) => deps.api.canonical_address(&treasury)?,
+        None => owner.clone(),
+    };
+    if let Some(oracle) = &msg.oracle {
+        deps.api.canonical_address(oracle)?;
+    }
+    let fundraise_asset = match msg.fundraise_cw20 {
+        Some(contract_addr) => {
+            deps.api.canonical_address(&contract_addr)?;
+            FundraiseAsset::Cw20 { contract_addr }
+        }
+        None => FundraiseAsset::Native {
+            denom: msg.fundraise_denom,
+        },
+    };
+
     //setup ICO base information
     let ico_info = ICOInfo {
         fundraise_goal: msg.fundraise_goal,
-        fundraise_bal: Uint128(0),
         base_conv_ratio: base_ratio,
-        owner: deps.api.canonical_address(&info.sender)?,
+        owner,
         fundraising_open: true,
-        fundraise_denom: msg.fundraise_denom,
-        fundraisers: vec![],
+        fundraise_asset,
+        accepted_denoms: msg.accepted_denoms.unwrap_or_default(),
+        oracle: msg.oracle,
         rates: ico_rates,
+        tier_mode: msg.tier_mode,
+        start: msg.start,
+        end: msg.end.unwrap_or_default(),
+        soft_cap: msg.soft_cap,
+        hard_cap: msg.hard_cap,
+        treasury,
+        withdrawn: Uint128(0),
+        vesting: msg.vesting,
+        closed_at: None,
+        tokens_for_sale: msg.tokens_for_sale,
+        min_contribution: msg.min_contribution,
+        max_contribution: msg.max_contribution,
+        allowlisted: msg.allowlisted,
+        merkle_root: msg.merkle_root,
+        kyc_required: msg.kyc_required,
+        attesters: vec![],
+        dutch_auction: msg.dutch_auction,
+        batch_auction: msg.batch_auction,
+        batch_clearing: None,
+        commit_reveal: msg.commit_reveal,
+        bonding_curve: msg.bonding_curve,
     };
 
     ICO.save(deps.storage, &ico_info)?;
+    TOTALS.save(deps.storage, &Totals::default())?;
+
+    for reserve in msg.reserves.unwrap_or_default() {
+        let key = deps.api.canonical_address(&reserve.address)?;
+        let reserve = Reserve {
+            address: reserve.address,
+            amount: reserve.amount,
+            vesting: reserve.vesting,
+            allocation: Uint128(0),
+            claimed: Uint128(0),
+        };
+        RESERVES.save(deps.storage, key.as_slice(), &reserve)?;
+    }
 
     // store token info using cw20-base format
     let token_info = TokenInfo {
@@ -65,7 +143,7 @@ pub fn instantiate(
         // set self as minter, so we can properly execute mint and burn
         mint: Some(MinterData {
             minter: deps.api.canonical_address(&env.contract.address)?,
-            cap: None,
+            cap: msg.max_supply,
         }),
     };
     TOKEN_INFO.save(deps.storage, &token_info)?;
@@ -81,52 +159,563 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::AddFunds {} => execute_add_funds(deps, info),
+        ExecuteMsg::AddFunds {
+            allocation,
+            attestation,
+        } => execute_add_funds(deps, env, info, Purchase::Contribution, allocation, attestation),
+        ExecuteMsg::PlaceBid {
+            max_price,
+            allocation,
+            attestation,
+        } => execute_add_funds(deps, env, info, Purchase::Bid { max_price }, allocation, attestation),
+        ExecuteMsg::Buy {
+            min_tokens,
+            allocation,
+            attestation,
+        } => execute_add_funds(deps, env, info, Purchase::Buy { min_tokens }, allocation, attestation),
+        ExecuteMsg::Sell { amount, min_return } => execute_sell(deps, env, info, amount, min_return),
+        ExecuteMsg::Commit { commitment } => execute_commit(deps, env, info, commitment),
+        ExecuteMsg::Reveal {
+            amount,
+            salt,
+            max_price,
+            allocation,
+            attestation,
+        } => {
+            let payload = CommitmentPayload {
+                address: info.sender.clone(),
+                amount,
+                max_price,
+                salt,
+            };
+            execute_reveal(deps, env, info, payload, allocation, attestation)
+        }
+        ExecuteMsg::ReclaimCommitment {} => execute_reclaim_commitment(deps, env, info),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
         ExecuteMsg::CloseFundraise {} => execute_close_fundraise(deps, env, &info.sender),
         ExecuteMsg::_SendTokens {} => _send_tokens(deps, env, info),
+        ExecuteMsg::ClaimRefund {} => execute_claim_refund(deps, info),
+        ExecuteMsg::ClaimTokens {} => execute_claim_tokens(deps, env, info),
+        ExecuteMsg::ClaimVested {} => execute_claim_vested(deps, env, info),
+        ExecuteMsg::ClaimReserve {} => execute_claim_reserve(deps, env, info),
+        ExecuteMsg::WithdrawProceeds { amount } => execute_withdraw_proceeds(deps, info, amount),
+        ExecuteMsg::UpdateTreasury { treasury } => execute_update_treasury(deps, info, treasury),
+        ExecuteMsg::UpdateAttesters { add, remove } => {
+            execute_update_attesters(deps, info, add, remove)
+        }
+        ExecuteMsg::UpdateMerkleRoot { merkle_root } => {
+            execute_update_merkle_root(deps, info, merkle_root)
+        }
+        ExecuteMsg::UpdateAllowlist { add, remove } => {
+            execute_update_allowlist(deps, info, add, remove)
+        }
 
         ExecuteMsg::Transfer { recipient, amount } => {
             Ok(execute_transfer(deps, env, info, recipient, amount)?)
         }
+        ExecuteMsg::Burn { amount } => Ok(execute_burn(deps, env, info, amount)?),
+        ExecuteMsg::Send {
+            contract,
+            amount,
+            msg,
+        } => Ok(execute_send(deps, env, info, contract, amount, msg)?),
+        ExecuteMsg::IncreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => Ok(execute_increase_allowance(
+            deps, env, info, spender, amount, expires,
+        )?),
+        ExecuteMsg::DecreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => Ok(execute_decrease_allowance(
+            deps, env, info, spender, amount, expires,
+        )?),
+        ExecuteMsg::TransferFrom {
+            owner,
+            recipient,
+            amount,
+        } => Ok(execute_transfer_from(
+            deps, env, info, owner, recipient, amount,
+        )?),
+        ExecuteMsg::SendFrom {
+            owner,
+            contract,
+            amount,
+            msg,
+        } => Ok(execute_send_from(
+            deps, env, info, owner, contract, amount, msg,
+        )?),
+        ExecuteMsg::BurnFrom { owner, amount } => {
+            Ok(execute_burn_from(deps, env, info, owner, amount)?)
+        }
     }
 }
 
+/// what a contribution is made as, every kind of sale only takes one of these
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Purchase {
+    /// plain contribution priced by the rates or a Dutch auction
+    Contribution,
+    /// batch auction bid paying at most max_price per token
+    Bid { max_price: Decimal },
+    /// bonding curve buy minting at least min_tokens
+    Buy { min_tokens: Option<Uint128> },
+}
+
 pub fn execute_add_funds(
     deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    purchase: Purchase,
+    allocation: Option<AllocationProof>,
+    attestation: Option<Attestation>,
+) -> Result<Response, ContractError> {
+    let ico_info = ICO.load(deps.storage)?;
+    //cw20 sales only take contributions through Receive
+    let denom = match &ico_info.fundraise_asset {
+        FundraiseAsset::Native { denom } => denom,
+        FundraiseAsset::Cw20 { .. } => return Err(ContractError::InvalidFundraiseAsset {}),
+    };
+    if ico_info.commit_reveal.is_some() {
+        return Err(ContractError::CommitRequired {});
+    }
+
+    //every accepted coin counts for what it is worth in the fundraise denom
+    let mut deposits = NativeBalance::default();
+    let mut amount = Uint128(0);
+    for payment in info.funds.iter().filter(|x| !x.amount.is_zero()) {
+        if &payment.denom == denom {
+            amount += payment.amount;
+        } else if ico_info.accepted_denoms.contains(&payment.denom) {
+            amount += payment.amount * oracle_price(deps.as_ref(), &ico_info, &payment.denom, denom)?;
+        } else {
+            //nothing could refund or withdraw a coin the sale doesn't track
+            return Err(ContractError::InvalidFundraiseAsset {});
+        }
+        deposits += payment.clone();
+    }
+    if amount.is_zero() {
+        return Err(ContractError::EmptyBalance {});
+    }
+
+    add_funds(deps, env, info.sender.clone(), amount, deposits, purchase, allocation, attestation)
+}
+
+/// what one unit of denom is worth in quote, according to the sale's oracle
+fn oracle_price(deps: Deps, ico_info: &ICOInfo, denom: &str, quote: &str) -> Result<Decimal, ContractError> {
+    let oracle = ico_info.oracle.clone().ok_or(ContractError::MissingOracle {})?;
+    let res: PriceResponse = deps.querier.query_wasm_smart(
+        oracle,
+        &OracleQueryMsg::Price {
+            denom: denom.to_string(),
+            quote: quote.to_string(),
+        },
+    )?;
+    Ok(res.rate)
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
     info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let ico_info = ICO.load(deps.storage)?;
+    //only the sale's own token can contribute, the cw20 contract is the one calling us
+    match &ico_info.fundraise_asset {
+        FundraiseAsset::Cw20 { contract_addr } if *contract_addr == info.sender => {}
+        _ => return Err(ContractError::InvalidFundraiseAsset {}),
+    }
+
+    let msg = match wrapper.msg {
+        Some(msg) => from_binary(&msg)?,
+        None => ReceiveMsg::AddFunds {
+            allocation: None,
+            attestation: None,
+        },
+    };
+    let (purchase, allocation, attestation) = match msg {
+        ReceiveMsg::Commit { commitment } => {
+            return commit(deps, env, wrapper.sender, wrapper.amount, commitment)
+        }
+        _ if ico_info.commit_reveal.is_some() => return Err(ContractError::CommitRequired {}),
+        ReceiveMsg::AddFunds {
+            allocation,
+            attestation,
+        } => (Purchase::Contribution, allocation, attestation),
+        ReceiveMsg::PlaceBid {
+            max_price,
+            allocation,
+            attestation,
+        } => (Purchase::Bid { max_price }, allocation, attestation),
+        ReceiveMsg::Buy {
+            min_tokens,
+            allocation,
+            attestation,
+        } => (Purchase::Buy { min_tokens }, allocation, attestation),
+    };
+    add_funds(
+        deps,
+        env,
+        wrapper.sender,
+        wrapper.amount,
+        NativeBalance::default(),
+        purchase,
+        allocation,
+        attestation,
+    )
+}
+
+/// takes amount worth of the fundraising asset from sender, however it arrived. deposits are the native
+/// coins it was paid in, empty for cw20 sales. bonding curve buys are minted to sender on the spot
+#[allow(clippy::too_many_arguments)]
+fn add_funds(
+    mut deps: DepsMut,
+    env: Env,
+    sender: HumanAddr,
+    amount: Uint128,
+    deposits: NativeBalance,
+    purchase: Purchase,
+    allocation: Option<AllocationProof>,
+    attestation: Option<Attestation>,
 ) -> Result<Response, ContractError> {
     let mut ico_info = ICO.load(deps.storage)?;
-    if ico_info.fundraising_open == false {
-        return Err(ContractError::FundraiseClosed {});
+    let mut totals = TOTALS.load(deps.storage)?;
+    check_open(&ico_info, &totals, &env.block)?;
+    check_purchase(&ico_info, purchase)?;
+
+    let key = deps.api.canonical_address(&sender)?;
+    let mut fundraiser = match FUNDRAISERS.may_load(deps.storage, key.as_slice())? {
+        Some(fundraiser) => fundraiser,
+        None => {
+            totals.fundraisers += 1;
+            Fundraiser {
+                source: sender.clone(),
+                balance: Uint128(0),
+                refunded: Uint128(0),
+                allocation: Uint128(0),
+                claimed: Uint128(0),
+                deposits: NativeBalance::default(),
+            }
+        }
+    };
+
+    let max = contribution_cap(deps.storage, &ico_info, &sender, &key, allocation.as_ref())?;
+    let mut accepted = accept_contribution(&ico_info, &totals, &env.block, fundraiser.balance, max, amount)?;
+    check_attestation(deps.api, &ico_info, &env, &sender, fundraiser.balance + accepted, attestation.as_ref())?;
+
+    //a curve buy only keeps what its tokens cost, the rest goes back along with anything over the hard cap
+    let mut bought = Uint128(0);
+    if let (Some(curve), Purchase::Buy { min_tokens }) = (ico_info.bonding_curve, purchase) {
+        let (tokens, cost) = curve
+            .buy(totals.curve_supply, accepted)
+            .ok_or(ContractError::BeyondCurve {})?;
+        if tokens.is_zero() {
+            return Err(ContractError::BuyTooSmall {});
+        }
+        if let Some(min) = min_tokens.filter(|min| tokens < *min) {
+            return Err(ContractError::SlippageExceeded { amount: tokens, min });
+        }
+        totals.curve_supply += tokens;
+        accepted = cost;
+        bought = tokens;
     }
+    let returned = Uint128(amount.u128() - accepted.u128());
 
-    let payment = info
-        .funds
-        .iter()
-        .find(|x| x.denom == ico_info.fundraise_denom && !x.amount.is_zero())
-        .ok_or_else(|| ContractError::EmptyBalance {})?;
+    //keep the running allocation total in step with this fundraiser's new allocation
+    let allocation =
+        ico_info.allocation_after(&totals, fundraiser.balance, fundraiser.allocation, accepted, env.block.time) + bought;
+    totals.allocated = Uint128(totals.allocated.u128() - fundraiser.allocation.u128() + allocation.u128());
+    totals.fundraise_bal += accepted;
+    fundraiser.balance += accepted;
+    fundraiser.allocation = allocation;
+    fundraiser.claimed += bought;
 
-    let index = ico_info.fundraisers.iter().enumerate().find_map(|(i, exist)| {
-        if exist.source == info.sender {
-            Some(i)
-        } else {
-            None
+    if let Purchase::Bid { max_price } = purchase {
+        let bid = Bid {
+            id: totals.bids,
+            bidder: sender.clone(),
+            amount: accepted,
+            max_price,
+        };
+        BIDS.save(deps.storage, U64Key::from(bid.id), &bid)?;
+        totals.bids += 1;
+    }
+
+    //whatever the hard cap turned away goes back in the same coins it came in
+    for kept in coins_between(&deposits, Uint128(0), accepted, amount) {
+        fundraiser.deposits += kept.clone();
+        totals.deposits += kept;
+    }
+    let returned_coins = coins_between(&deposits, accepted, amount, amount);
+
+    FUNDRAISERS.save(deps.storage, key.as_slice(), &fundraiser)?;
+    TOTALS.save(deps.storage, &totals)?;
+
+    if !bought.is_zero() {
+        let sub_info = MessageInfo {
+            sender: env.contract.address.clone(),
+            funds: vec![],
+        };
+        execute_mint(deps.branch(), env.clone(), sub_info, sender.clone(), bought)?;
+    }
+
+    let mut messages = pay_out(&ico_info.fundraise_asset, sender.clone(), returned, returned_coins)?;
+
+    //hitting the hard cap, or selling every token an auction has on offer, closes the sale on the spot
+    let sold_out = ico_info.auction_tokens_left(&totals).map_or(false, |left| left.is_zero());
+    let capped = sold_out || ico_info.hard_cap.map_or(false, |cap| totals.fundraise_bal >= cap);
+    if capped {
+        ico_info.fundraising_open = false;
+        ico_info.closed_at = Some(env.block.time);
+        ICO.save(deps.storage, &ico_info)?;
+        messages.push(send_tokens_msg(&env)?);
+    }
+
+    let res = Response {
+        messages,
+        attributes: vec![
+            attr("action", "add_funds"),
+            attr("id", sender.as_str()),
+            attr("amount", accepted),
+            attr("returned", returned),
+            attr("closed", capped),
+            attr("minted", bought),
+        ],
+        ..Response::default()
+    };
+    Ok(res)
+}
+
+pub fn execute_commit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    commitment: Binary,
+) -> Result<Response, ContractError> {
+    let ico_info = ICO.load(deps.storage)?;
+    //commitments only escrow the fundraise denom, other accepted denoms can't be priced until the reveal
+    let denom = match &ico_info.fundraise_asset {
+        FundraiseAsset::Native { denom } => denom,
+        FundraiseAsset::Cw20 { .. } => return Err(ContractError::InvalidFundraiseAsset {}),
+    };
+    if info.funds.iter().any(|x| &x.denom != denom) {
+        return Err(ContractError::InvalidFundraiseAsset {});
+    }
+    let amount = info.funds.iter().fold(Uint128(0), |total, x| total + x.amount);
+
+    commit(deps, env, info.sender, amount, commitment)
+}
+
+/// escrows amount of the fundraising asset from sender against the hash of what they'll reveal
+fn commit(
+    deps: DepsMut,
+    env: Env,
+    sender: HumanAddr,
+    amount: Uint128,
+    commitment: Binary,
+) -> Result<Response, ContractError> {
+    let ico_info = ICO.load(deps.storage)?;
+    let mut totals = TOTALS.load(deps.storage)?;
+    let commit_reveal = ico_info.commit_reveal.ok_or(ContractError::NotCommitReveal {})?;
+    check_open(&ico_info, &totals, &env.block)?;
+    if env.block.height >= commit_reveal.commit_end {
+        return Err(ContractError::CommitPhaseOver {
+            height: commit_reveal.commit_end,
+        });
+    }
+    if amount.is_zero() {
+        return Err(ContractError::EmptyBalance {});
+    }
+    if commitment.as_slice().len() != 32 {
+        return Err(ContractError::InvalidCommitment {});
+    }
+
+    let key = deps.api.canonical_address(&sender)?;
+    if COMMITMENTS.may_load(deps.storage, key.as_slice())?.is_some() {
+        return Err(ContractError::CommitmentExists {});
+    }
+    let commitment = Commitment {
+        owner: sender.clone(),
+        hash: commitment,
+        escrow: amount,
+    };
+    COMMITMENTS.save(deps.storage, key.as_slice(), &commitment)?;
+    totals.escrowed += amount;
+    TOTALS.save(deps.storage, &totals)?;
+
+    let res = Response {
+        attributes: vec![
+            attr("action", "commit"),
+            attr("id", sender.as_str()),
+            attr("escrow", amount),
+        ],
+        ..Response::default()
+    };
+    Ok(res)
+}
+
+pub fn execute_reveal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    payload: CommitmentPayload,
+    allocation: Option<AllocationProof>,
+    attestation: Option<Attestation>,
+) -> Result<Response, ContractError> {
+    let ico_info = ICO.load(deps.storage)?;
+    let mut totals = TOTALS.load(deps.storage)?;
+    let commit_reveal = ico_info.commit_reveal.ok_or(ContractError::NotCommitReveal {})?;
+    if env.block.height < commit_reveal.commit_end || env.block.height >= commit_reveal.reveal_end {
+        return Err(ContractError::NotRevealPhase {
+            start: commit_reveal.commit_end,
+            end: commit_reveal.reveal_end,
+        });
+    }
+
+    let key = deps.api.canonical_address(&info.sender)?;
+    let commitment = COMMITMENTS
+        .may_load(deps.storage, key.as_slice())?
+        .ok_or_else(|| ContractError::NoCommitment {})?;
+    if commitment_hash(&payload)? != commitment.hash.as_slice() {
+        return Err(ContractError::InvalidReveal {});
+    }
+    if payload.amount > commitment.escrow {
+        return Err(ContractError::RevealAboveEscrow {});
+    }
+    COMMITMENTS.remove(deps.storage, key.as_slice());
+    totals.escrowed = Uint128(totals.escrowed.u128() - commitment.escrow.u128());
+    TOTALS.save(deps.storage, &totals)?;
+
+    //revealing nothing backs out of the sale without the penalty
+    let mut res = if payload.amount.is_zero() {
+        Response {
+            attributes: vec![attr("action", "reveal"), attr("id", info.sender.as_str())],
+            ..Response::default()
         }
-    });
+    } else {
+        let deposits = NativeBalance(asset_coins(&ico_info.fundraise_asset, payload.amount));
+        let purchase = match payload.max_price {
+            Some(max_price) => Purchase::Bid { max_price },
+            None => Purchase::Contribution,
+        };
+        add_funds(
+            deps,
+            env,
+            info.sender.clone(),
+            payload.amount,
+            deposits,
+            purchase,
+            allocation,
+            attestation,
+        )?
+    };
 
-    match index {
-        Some(idx) => ico_info.fundraisers[idx].balance += payment.amount,
-        None => ico_info.fundraisers.push(Fundraiser {
-            source: info.sender.clone(),
-            balance: payment.amount,
-        }),
+    //escrow beyond the revealed amount only hid how much was coming
+    let excess = Uint128(commitment.escrow.u128() - payload.amount.u128());
+    let native = asset_coins(&ico_info.fundraise_asset, excess);
+    res.messages.extend(pay_out(&ico_info.fundraise_asset, info.sender, excess, native)?);
+    res.attributes.push(attr("escrow_returned", excess));
+    Ok(res)
+}
+
+pub fn execute_reclaim_commitment(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let ico_info = ICO.load(deps.storage)?;
+    let mut totals = TOTALS.load(deps.storage)?;
+    let commit_reveal = ico_info.commit_reveal.ok_or(ContractError::NotCommitReveal {})?;
+
+    let key = deps.api.canonical_address(&info.sender)?;
+    let commitment = COMMITMENTS
+        .may_load(deps.storage, key.as_slice())?
+        .ok_or_else(|| ContractError::NoCommitment {})?;
+    let revealable = env.block.height < commit_reveal.reveal_end;
+    if revealable && ico_info.fundraising_open {
+        return Err(ContractError::CommitmentLocked {});
+    }
+    COMMITMENTS.remove(deps.storage, key.as_slice());
+    totals.escrowed = Uint128(totals.escrowed.u128() - commitment.escrow.u128());
+    TOTALS.save(deps.storage, &totals)?;
+
+    //only commitments that sat through the whole reveal window are penalised
+    let penalty = if revealable {
+        Uint128(0)
+    } else {
+        commitment.escrow * commit_reveal.penalty
+    };
+    let refund = Uint128(commitment.escrow.u128() - penalty.u128());
+    let asset = &ico_info.fundraise_asset;
+    let mut messages = pay_out(asset, info.sender.clone(), refund, asset_coins(asset, refund))?;
+    let treasury = deps.api.human_address(&ico_info.treasury)?;
+    messages.extend(pay_out(asset, treasury, penalty, asset_coins(asset, penalty))?);
+
+    let res = Response {
+        messages,
+        attributes: vec![
+            attr("action", "reclaim_commitment"),
+            attr("to", info.sender),
+            attr("amount", refund),
+            attr("penalty", penalty),
+        ],
+        ..Response::default()
+    };
+    Ok(res)
+}
+
+pub fn execute_sell(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    min_return: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let ico_info = ICO.load(deps.storage)?;
+    let mut totals = TOTALS.load(deps.storage)?;
+    let curve = ico_info.bonding_curve.ok_or(ContractError::NotBondingCurve {})?;
+    check_open(&ico_info, &totals, &env.block)?;
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let payout = curve
+        .sell(totals.curve_supply, amount)
+        .ok_or(ContractError::BeyondCurve {})?;
+    if let Some(min) = min_return.filter(|min| payout < *min) {
+        return Err(ContractError::SlippageExceeded { amount: payout, min });
+    }
+    //burning fails unless the sender actually holds the tokens
+    execute_burn(deps.branch(), env, info.clone(), amount)?;
+
+    //the reserve is exactly what the curve needs at its supply, so it always covers the payout
+    let asset = &ico_info.fundraise_asset;
+    totals.curve_supply = Uint128(totals.curve_supply.u128() - amount.u128());
+    totals.fundraise_bal = Uint128(totals.fundraise_bal.u128() - payout.u128());
+    totals.deposits = (totals.deposits.clone() - asset_coins(asset, payout))?;
+    TOTALS.save(deps.storage, &totals)?;
+
+    //what's paid back stops counting towards the seller's contribution limits. tokens bought from someone
+    //else can pay out more than the seller put in, and curve sales only take the fundraise asset
+    let key = deps.api.canonical_address(&info.sender)?;
+    if let Some(mut fundraiser) = FUNDRAISERS.may_load(deps.storage, key.as_slice())? {
+        fundraiser.balance = Uint128(fundraiser.balance.u128().saturating_sub(payout.u128()));
+        fundraiser.deposits = NativeBalance(asset_coins(asset, fundraiser.balance));
+        FUNDRAISERS.save(deps.storage, key.as_slice(), &fundraiser)?;
     }
-    
-    ico_info.fundraise_bal += payment.amount;
-    ICO.save(deps.storage, &ico_info)?;
 
     let res = Response {
-        attributes: vec![attr("action", "add_funds"), attr("id", info.sender.as_str())],
+        messages: pay_out(asset, info.sender.clone(), payout, asset_coins(asset, payout))?,
+        attributes: vec![
+            attr("action", "sell"),
+            attr("from", info.sender),
+            attr("amount", amount),
+            attr("returned", payout),
+        ],
         ..Response::default()
     };
     Ok(res)
@@ -139,27 +728,22 @@ pub fn execute_close_fundraise(
 ) -> Result<Response, ContractError> {
 
     let mut ico_info = ICO.load(deps.storage)?;
+    let totals = TOTALS.load(deps.storage)?;
     let canonical = deps.api.canonical_address(sender)?;
 
     if ico_info.fundraising_open {
-        if canonical == ico_info.owner || ico_info.fundraise_bal >= ico_info.fundraise_goal {
+        if canonical == ico_info.owner
+            || totals.fundraise_bal >= ico_info.fundraise_goal
+            || ico_info.end.is_expired(&env.block)
+        {
             ico_info.fundraising_open = false;
+            ico_info.closed_at = Some(env.block.time);
             ICO.save(deps.storage, &ico_info)?;
             
             //fundraising is closed, send callback to send everyone their cw20 tokens
-            let contract_addr = env.contract.address;
-            let msg = to_binary(&ExecuteMsg::_SendTokens {})?;
-
             let res = Response {
                 submessages: vec![],
-                messages: vec![
-                    WasmMsg::Execute {
-                        contract_addr,
-                        msg,
-                        send: vec![],
-                    }
-                    .into(),
-                ],
+                messages: vec![send_tokens_msg(&env)?],
                 attributes: vec![attr("action", "close_fundraise")],
                 data: None,
             };
@@ -171,6 +755,262 @@ pub fn execute_close_fundraise(
     return Err(ContractError::FundraiseClosed {});
 }
 
+/// errors unless the sale is currently taking contributions
+fn check_open(ico_info: &ICOInfo, totals: &Totals, block: &BlockInfo) -> Result<(), ContractError> {
+    match sale_status(ico_info, totals, block) {
+        SaleStatus::Open => Ok(()),
+        SaleStatus::Pending => Err(ContractError::FundraiseNotStarted {}),
+        SaleStatus::Ended => Err(ContractError::FundraiseEnded {}),
+        SaleStatus::Closed | SaleStatus::Failed => Err(ContractError::FundraiseClosed {}),
+    }
+}
+
+/// batch auctions only take bids between the reserve price and the most the atomics math handles,
+/// bonding curves only take buys and every other sale only takes plain contributions
+fn check_purchase(ico_info: &ICOInfo, purchase: Purchase) -> Result<(), ContractError> {
+    match (&ico_info.batch_auction, &ico_info.bonding_curve, purchase) {
+        (Some(auction), _, Purchase::Bid { max_price })
+            if max_price < auction.reserve_price || !is_valid_price(max_price) =>
+        {
+            Err(ContractError::InvalidBidPrice {})
+        }
+        (Some(_), _, Purchase::Bid { .. }) => Ok(()),
+        (Some(_), _, _) => Err(ContractError::BidRequired {}),
+        (None, _, Purchase::Bid { .. }) => Err(ContractError::NotBatchAuction {}),
+        (None, Some(_), Purchase::Buy { .. }) => Ok(()),
+        (None, Some(_), Purchase::Contribution) => Err(ContractError::BuyRequired {}),
+        (None, None, Purchase::Buy { .. }) => Err(ContractError::NotBondingCurve {}),
+        (None, None, Purchase::Contribution) => Ok(()),
+    }
+}
+
+/// how much of a contribution the sale takes right now, shared by AddFunds and SimulateContribution
+fn accept_contribution(
+    ico_info: &ICOInfo,
+    totals: &Totals,
+    block: &BlockInfo,
+    balance: Uint128,
+    max: Option<Uint128>,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    check_open(ico_info, totals, block)?;
+    if amount.is_zero() {
+        return Err(ContractError::EmptyBalance {});
+    }
+
+    //per address limits apply to everything the address has put in so far
+    let new_balance = balance + amount;
+    if let Some(min) = ico_info.min_contribution {
+        if new_balance < min {
+            return Err(ContractError::BelowMinContribution { min });
+        }
+    }
+    if let Some(max) = max {
+        if new_balance > max {
+            return Err(ContractError::AboveMaxContribution { max });
+        }
+    }
+
+    //only take what fits under the hard cap, the rest goes straight back
+    let accepted = match ico_info.hard_cap {
+        Some(cap) if totals.fundraise_bal + amount > cap => {
+            Uint128(cap.u128() - totals.fundraise_bal.u128())
+        }
+        _ => amount,
+    };
+    //a Dutch auction only sells the tokens it has left, whatever would buy past them goes back too
+    let accepted = match (&ico_info.dutch_auction, ico_info.auction_tokens_left(totals)) {
+        (Some(auction), Some(left)) => accepted.min(cost_of(left, auction.price_at(block.time))),
+        _ => accepted,
+    };
+    //the trims can't leave the sender under the min either
+    if let Some(min) = ico_info.min_contribution {
+        if balance + accepted < min {
+            return Err(ContractError::BelowMinContribution { min });
+        }
+    }
+    Ok(accepted)
+}
+
+/// most an address can contribute in total, rejecting addresses missing from an enforced allowlist.
+/// a merkle root replaces the stored allowlist, the address proves its own cap instead
+fn contribution_cap(
+    storage: &dyn Storage,
+    ico_info: &ICOInfo,
+    address: &HumanAddr,
+    key: &CanonicalAddr,
+    allocation: Option<&AllocationProof>,
+) -> Result<Option<Uint128>, ContractError> {
+    if let Some(root) = &ico_info.merkle_root {
+        let allocation = allocation.ok_or_else(|| ContractError::MissingAllocationProof {})?;
+        if !verify_allocation(root, address, allocation) {
+            return Err(ContractError::InvalidAllocationProof {});
+        }
+        return Ok(tightest(ico_info.max_contribution, Some(allocation.cap)));
+    }
+    if !ico_info.allowlisted {
+        return Ok(ico_info.max_contribution);
+    }
+    let entry = ALLOWLIST
+        .may_load(storage, key.as_slice())?
+        .ok_or_else(|| ContractError::NotAllowlisted {})?;
+    Ok(tightest(ico_info.max_contribution, entry.cap))
+}
+
+/// sha256 of the JSON payload a commit-reveal contributor commits to
+pub fn commitment_hash(payload: &CommitmentPayload) -> StdResult<Vec<u8>> {
+    Ok(Sha256::digest(&to_vec(payload)?).to_vec())
+}
+
+/// sha256 of the JSON payload an attester signs
+pub fn attestation_hash(payload: &AttestationPayload) -> StdResult<Vec<u8>> {
+    Ok(Sha256::digest(&to_vec(payload)?).to_vec())
+}
+
+/// on KYC sales, checks a registered attester signed off on sender contributing total so far
+fn check_attestation(
+    api: &dyn Api,
+    ico_info: &ICOInfo,
+    env: &Env,
+    sender: &HumanAddr,
+    total: Uint128,
+    attestation: Option<&Attestation>,
+) -> Result<(), ContractError> {
+    if !ico_info.kyc_required {
+        return Ok(());
+    }
+    let attestation = attestation.ok_or_else(|| ContractError::AttestationRequired {})?;
+    if attestation.address != *sender {
+        return Err(ContractError::InvalidAttestation {});
+    }
+
+    let hash = attestation_hash(&attestation.payload(env.contract.address.clone()))?;
+    let signed = ico_info.attesters.iter().any(|pubkey| {
+        api.secp256k1_verify(&hash, attestation.signature.as_slice(), pubkey.as_slice())
+            .unwrap_or(false)
+    });
+    if !signed {
+        return Err(ContractError::InvalidAttestation {});
+    }
+
+    if attestation.expires.is_expired(&env.block) {
+        return Err(ContractError::AttestationExpired {});
+    }
+    if total > attestation.max_amount {
+        return Err(ContractError::AboveAttestedAmount { max: attestation.max_amount });
+    }
+    Ok(())
+}
+
+/// the tighter of two optional caps
+fn tightest(a: Option<Uint128>, b: Option<Uint128>) -> Option<Uint128> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// if address and cap are a leaf of the merkle root, anything that isn't a 32 byte hash fails
+fn verify_allocation(root: &Binary, address: &HumanAddr, allocation: &AllocationProof) -> bool {
+    let root: Hash = match root.as_slice().try_into() {
+        Ok(root) => root,
+        Err(_) => return false,
+    };
+    let proof: Option<Vec<Hash>> = allocation
+        .proof
+        .iter()
+        .map(|sibling| sibling.as_slice().try_into().ok())
+        .collect();
+    match proof {
+        Some(proof) => verify(&root, leaf_hash(address, allocation.cap), &proof),
+        None => false,
+    }
+}
+
+/// sends recipient amount of the cw20 token, or native for native sales. empty if there's nothing to send
+fn pay_out(
+    asset: &FundraiseAsset,
+    recipient: HumanAddr,
+    amount: Uint128,
+    native: Vec<Coin>,
+) -> StdResult<Vec<CosmosMsg>> {
+    let msg = match asset {
+        FundraiseAsset::Native { .. } if native.is_empty() => return Ok(vec![]),
+        FundraiseAsset::Native { .. } => BankMsg::Send {
+            to_address: recipient,
+            amount: native,
+        }
+        .into(),
+        FundraiseAsset::Cw20 { .. } if amount.is_zero() => return Ok(vec![]),
+        FundraiseAsset::Cw20 { contract_addr } => WasmMsg::Execute {
+            contract_addr: contract_addr.clone(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient, amount })?,
+            send: vec![],
+        }
+        .into(),
+    };
+    Ok(vec![msg])
+}
+
+/// amount of a native sale's fundraise denom as coins, empty for cw20 sales or nothing at all
+fn asset_coins(asset: &FundraiseAsset, amount: Uint128) -> Vec<Coin> {
+    match asset {
+        FundraiseAsset::Native { denom } if !amount.is_zero() => vec![coin(amount.u128(), denom.clone())],
+        _ => vec![],
+    }
+}
+
+/// part of deposits between the from and to fractions of total. paying out in steps along the same total
+/// rounds every step down from a running share, so the steps never add up to more than was deposited
+fn coins_between(deposits: &NativeBalance, from: Uint128, to: Uint128, total: Uint128) -> Vec<Coin> {
+    deposits
+        .0
+        .iter()
+        .map(|c| {
+            let paid = c.amount.multiply_ratio(from, total);
+            let owed = c.amount.multiply_ratio(to, total);
+            coin(owed.u128() - paid.u128(), c.denom.clone())
+        })
+        .filter(|c| !c.amount.is_zero())
+        .collect()
+}
+
+/// callback the contract sends itself once fundraising is closed
+fn send_tokens_msg(env: &Env) -> StdResult<CosmosMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: env.contract.address.clone(),
+        msg: to_binary(&ExecuteMsg::_SendTokens {})?,
+        send: vec![],
+    }
+    .into())
+}
+
+/// where the sale is in its window at the given block
+pub fn sale_status(ico_info: &ICOInfo, totals: &Totals, block: &BlockInfo) -> SaleStatus {
+    if ico_info.is_failed(totals) {
+        SaleStatus::Failed
+    } else if !ico_info.fundraising_open {
+        SaleStatus::Closed
+    } else if ico_info.end.is_expired(block) {
+        SaleStatus::Ended
+    } else if ico_info.start.as_ref().map_or(false, |start| !start.is_expired(block)) {
+        SaleStatus::Pending
+    } else {
+        SaleStatus::Open
+    }
+}
+
+/// which commit-reveal phase the sale is in at the given block
+pub fn commit_phase(commit_reveal: &CommitReveal, block: &BlockInfo) -> CommitPhase {
+    if block.height < commit_reveal.commit_end {
+        CommitPhase::Commit
+    } else if block.height < commit_reveal.reveal_end {
+        CommitPhase::Reveal
+    } else {
+        CommitPhase::Ended
+    }
+}
+
 pub fn _send_tokens(
     mut deps: DepsMut,
     env: Env,
@@ -182,36 +1022,59 @@ pub fn _send_tokens(
         return Err(ContractError::Unauthorized {});
     }
 
-    let ico_info = ICO.load(deps.storage)?;
-
-    //iter through fundraisers and send them right number of tokens
-    let mut messages = vec![];
-    let mut to_mint = Uint128(0);
+    let mut ico_info = ICO.load(deps.storage)?;
+    let mut totals = TOTALS.load(deps.storage)?;
 
-    for f in &ico_info.fundraisers {
-        let f_rate = ico_info.rates.iter().find(|r| f.balance >= r.min);
-        let ratio = match f_rate {
-            Some(r) => r.ratio,
-            None => ico_info.base_conv_ratio,
+    //soft cap missed, nothing gets minted and everyone claims a refund instead
+    if ico_info.is_failed(&totals) {
+        let res = Response {
+            attributes: vec![attr("action", "send_tokens"), attr("refunds_enabled", "true")],
+            ..Response::default()
         };
+        return Ok(res);
+    }
 
-        let recieves = f.balance * ratio;
-        
-        to_mint += recieves;
+    //batch auction bids only turn into allocations once every bid is in
+    if let Some(auction) = ico_info.batch_auction {
+        settle_batch(deps.branch(), &mut ico_info, &mut totals, auction)?;
+    }
 
-        let binary_msg = to_binary(&ExecuteMsg::Transfer {
-            recipient: f.source.clone(),
-            amount: recieves,
-        })?;
+    //everyone's allocation is totalled as they contribute, fundraisers pull their share with ClaimTokens.
+    //bonding curve buys were minted as they came in, so only reserves are left to mint for those
+    let curve = ico_info.bonding_curve.is_some();
+    let mut to_mint = if curve { Uint128(0) } else { totals.allocated };
 
-        let wasm_exec = WasmMsg::Execute {
-                contract_addr: human_contract_address.clone(),
-                msg: binary_msg,
-                send: vec![],
-            }.into();
+    //oversubscribed fixed supply sales only hand out what's on offer, shared pro-rata when claimed
+    if let Some(tokens_for_sale) = ico_info.tokens_for_sale {
+        if to_mint > tokens_for_sale {
+            to_mint = tokens_for_sale;
+        }
+    }
 
-        messages.push(wasm_exec);
+    //reserves are sized against what contributors were allocated, or still hold off a curve, vesting
+    //ones stay with the contract
+    let reserves: Vec<(Vec<u8>, Reserve)> = RESERVES
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    let mut allocations = reserve_allocations(
+        &reserves.iter().map(|(_, r)| r.clone()).collect::<Vec<_>>(),
+        if curve { totals.curve_supply } else { to_mint },
+    );
+
+    //scale everything down pro-rata instead of minting past the cap, rounding down so the cap always holds
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    if let Some(cap) = token_info.get_cap() {
+        let available = Uint128(cap.u128().saturating_sub(token_info.total_supply.u128()));
+        let wanted = Uint128(allocations.iter().fold(to_mint.u128(), |total, a| total.saturating_add(a.u128())));
+        if wanted > available {
+            to_mint = to_mint.multiply_ratio(available, wanted);
+            for allocation in allocations.iter_mut() {
+                *allocation = allocation.multiply_ratio(available, wanted);
+            }
+        }
     }
+    totals.distributed = Some(if curve { totals.allocated } else { to_mint });
+    TOTALS.save(deps.storage, &totals)?;
 
     //mint required tokens to the contract itself
     // call into cw20-base to mint the token, call as self as no one else is allowed
@@ -220,371 +1083,3442 @@ pub fn _send_tokens(
         funds: vec![],
     };
 
-    execute_mint(deps.branch(), env, sub_info, human_contract_address.clone(), to_mint)?;
+    if !to_mint.is_zero() {
+        execute_mint(deps.branch(), env.clone(), sub_info.clone(), human_contract_address.clone(), to_mint)?;
+    }
+
+    let mut reserved = Uint128(0);
+    for ((key, mut reserve), allocation) in reserves.into_iter().zip(allocations) {
+        reserve.allocation = allocation;
+        let recipient = match reserve.vesting {
+            Some(_) => human_contract_address.clone(),
+            None => {
+                reserve.claimed = allocation;
+                reserve.address.clone()
+            }
+        };
+        if !allocation.is_zero() {
+            execute_mint(deps.branch(), env.clone(), sub_info.clone(), recipient, allocation)?;
+        }
+        RESERVES.save(deps.storage, &key, &reserve)?;
+        reserved = reserved + allocation;
+    }
 
-    //send
     let res = Response {
-        submessages: vec![],
-        messages,
-        attributes: vec![attr("action", "transfer")],
-        data: None,
+        attributes: vec![
+            attr("action", "send_tokens"),
+            attr("minted", to_mint),
+            attr("reserved", reserved),
+        ],
+        ..Response::default()
     };
 
     Ok(res)
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::TokenInfo {} => to_binary(&query_token_info(deps)?),
-        QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
-        QueryMsg::FundraiseInfo {} => to_binary(&query_fundraise(deps)?),
-        QueryMsg::StakedInfo {} => to_binary(&query_staked(deps)?),
+/// clears every bid at one price and allocates each bidder what their bids won
+fn settle_batch(
+    deps: DepsMut,
+    ico_info: &mut ICOInfo,
+    totals: &mut Totals,
+    auction: BatchAuction,
+) -> Result<(), ContractError> {
+    let bids: Vec<Bid> = BIDS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, bid)| bid))
+        .collect::<StdResult<_>>()?;
+    let clearing = clear_batch(&bids, ico_info.tokens_for_sale.unwrap_or_default(), auction.reserve_price);
 
+    let mut won: BTreeMap<HumanAddr, Uint128> = BTreeMap::new();
+    for bid in bids.iter() {
+        *won.entry(bid.bidder.clone()).or_default() += clearing.fill(bid);
     }
+    for (bidder, allocation) in won {
+        let key = deps.api.canonical_address(&bidder)?;
+        let mut fundraiser = FUNDRAISERS.load(deps.storage, key.as_slice())?;
+        fundraiser.allocation = allocation;
+        FUNDRAISERS.save(deps.storage, key.as_slice(), &fundraiser)?;
+    }
+
+    totals.allocated = clearing.tokens_sold;
+    ico_info.batch_clearing = Some(clearing);
+    ICO.save(deps.storage, ico_info)?;
+    Ok(())
 }
 
-pub fn query_fundraise(deps: Deps) -> StdResult<FundraiseInfoResponse> {
+pub fn execute_claim_tokens(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
     let ico_info = ICO.load(deps.storage)?;
+    let totals = TOTALS.load(deps.storage)?;
+    if ico_info.fundraising_open || ico_info.is_failed(&totals) {
+        return Err(ContractError::ClaimsUnavailable {});
+    }
+    if ico_info.vesting.is_some() {
+        return Err(ContractError::TokensVesting {});
+    }
+
+    let key = deps.api.canonical_address(&info.sender)?;
+    let mut fundraiser = FUNDRAISERS
+        .may_load(deps.storage, key.as_slice())?
+        .ok_or_else(|| ContractError::NoContribution {})?;
+
+    //losing auction bids contributed but never won anything to claim
+    let allocation = totals.share_of(fundraiser.allocation);
+    if allocation.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    let claim = Uint128(allocation.u128() - fundraiser.claimed.u128());
+    if claim.is_zero() {
+        return Err(ContractError::AlreadyClaimed {});
+    }
+    fundraiser.claimed = allocation;
+    FUNDRAISERS.save(deps.storage, key.as_slice(), &fundraiser)?;
+
+    release_tokens(deps, env, info.sender.clone(), claim)?;
+
+    let res = Response {
+        attributes: vec![
+            attr("action", "claim_tokens"),
+            attr("to", info.sender),
+            attr("amount", claim),
+        ],
+        ..Response::default()
+    };
+    Ok(res)
+}
+
+pub fn execute_claim_vested(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let ico_info = ICO.load(deps.storage)?;
+    let totals = TOTALS.load(deps.storage)?;
+    if ico_info.fundraising_open || ico_info.is_failed(&totals) {
+        return Err(ContractError::ClaimsUnavailable {});
+    }
+    let vesting = ico_info.vesting.ok_or_else(|| ContractError::NoVesting {})?;
+
+    let key = deps.api.canonical_address(&info.sender)?;
+    let mut fundraiser = FUNDRAISERS
+        .may_load(deps.storage, key.as_slice())?
+        .ok_or_else(|| ContractError::NoContribution {})?;
+
+    let allocation = totals.share_of(fundraiser.allocation);
+    let vested = vesting.vested(allocation, ico_info.closed_at.unwrap_or_default(), env.block.time);
+    let claim = Uint128(vested.u128() - fundraiser.claimed.u128());
+    if claim.is_zero() {
+        return Err(ContractError::NothingVested {});
+    }
+    fundraiser.claimed = vested;
+    FUNDRAISERS.save(deps.storage, key.as_slice(), &fundraiser)?;
+
+    release_tokens(deps, env, info.sender.clone(), claim)?;
+
+    let res = Response {
+        attributes: vec![
+            attr("action", "claim_vested"),
+            attr("to", info.sender),
+            attr("amount", claim),
+        ],
+        ..Response::default()
+    };
+    Ok(res)
+}
+
+pub fn execute_claim_reserve(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let ico_info = ICO.load(deps.storage)?;
+    let totals = TOTALS.load(deps.storage)?;
+    if ico_info.fundraising_open || ico_info.is_failed(&totals) {
+        return Err(ContractError::ClaimsUnavailable {});
+    }
+
+    let key = deps.api.canonical_address(&info.sender)?;
+    let mut reserve = RESERVES
+        .may_load(deps.storage, key.as_slice())?
+        .ok_or_else(|| ContractError::NoReserve {})?;
+    //reserves without vesting were minted straight to their address at close
+    let vesting = reserve.vesting.ok_or_else(|| ContractError::NoVesting {})?;
+
+    let vested = vesting.vested(reserve.allocation, ico_info.closed_at.unwrap_or_default(), env.block.time);
+    let claim = Uint128(vested.u128() - reserve.claimed.u128());
+    if claim.is_zero() {
+        return Err(ContractError::NothingVested {});
+    }
+    reserve.claimed = vested;
+    RESERVES.save(deps.storage, key.as_slice(), &reserve)?;
+
+    release_tokens(deps, env, info.sender.clone(), claim)?;
+
+    let res = Response {
+        attributes: vec![
+            attr("action", "claim_reserve"),
+            attr("to", info.sender),
+            attr("amount", claim),
+        ],
+        ..Response::default()
+    };
+    Ok(res)
+}
+
+/// tokens are minted to the contract at close, this moves a claim out of its cw20 balance
+fn release_tokens(
+    deps: DepsMut,
+    env: Env,
+    recipient: HumanAddr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let sub_info = MessageInfo {
+        sender: env.contract.address.clone(),
+        funds: vec![],
+    };
+    execute_transfer(deps, env, sub_info, recipient, amount)?;
+    Ok(())
+}
+
+pub fn execute_claim_refund(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let ico_info = ICO.load(deps.storage)?;
+    let totals = TOTALS.load(deps.storage)?;
+    let failed = ico_info.is_failed(&totals);
+    let scaled = !ico_info.fundraising_open && !totals.unused_of(totals.fundraise_bal).is_zero();
+    let settled = ico_info.clearing_price().is_some();
+    if !failed && !scaled && !settled {
+        return Err(ContractError::RefundsUnavailable {});
+    }
+
+    let key = deps.api.canonical_address(&info.sender)?;
+    let mut fundraiser = FUNDRAISERS
+        .may_load(deps.storage, key.as_slice())?
+        .ok_or_else(|| ContractError::NoContribution {})?;
+
+    //failed sales return everything, others whatever was bid above the clearing price or bought nothing
+    let owed = if failed {
+        fundraiser.balance
+    } else {
+        let spent = ico_info.spent(fundraiser.balance, fundraiser.allocation);
+        Uint128(fundraiser.balance.u128() - spent.u128()) + totals.unused_of(spent)
+    };
+    let refund = Uint128(owed.u128() - fundraiser.refunded.u128());
+    if refund.is_zero() {
+        return Err(ContractError::AlreadyRefunded {});
+    }
+    let native = coins_between(&fundraiser.deposits, fundraiser.refunded, owed, fundraiser.balance);
+    fundraiser.refunded = owed;
+    FUNDRAISERS.save(deps.storage, key.as_slice(), &fundraiser)?;
+
+    let res = Response {
+        messages: pay_out(&ico_info.fundraise_asset, info.sender.clone(), refund, native)?,
+        attributes: vec![
+            attr("action", "claim_refund"),
+            attr("to", info.sender),
+            attr("amount", refund),
+        ],
+        ..Response::default()
+    };
+    Ok(res)
+}
+
+pub fn execute_withdraw_proceeds(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let mut ico_info = ICO.load(deps.storage)?;
+    let totals = TOTALS.load(deps.storage)?;
+    if deps.api.canonical_address(&info.sender)? != ico_info.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if ico_info.fundraising_open || ico_info.is_failed(&totals) {
+        return Err(ContractError::ProceedsUnavailable {});
+    }
+
+    let remaining = ico_info.proceeds_remaining(&totals);
+    let amount = amount.unwrap_or(remaining);
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    if amount > remaining {
+        return Err(ContractError::InsufficientProceeds {});
+    }
+
+    //every denom raised is withdrawn in the same proportion
+    let withdrawn = ico_info.withdrawn + amount;
+    let native = coins_between(&totals.deposits, ico_info.withdrawn, withdrawn, totals.fundraise_bal);
+    ico_info.withdrawn = withdrawn;
+    ICO.save(deps.storage, &ico_info)?;
+
+    let treasury = deps.api.human_address(&ico_info.treasury)?;
+    let res = Response {
+        messages: pay_out(&ico_info.fundraise_asset, treasury.clone(), amount, native)?,
+        attributes: vec![
+            attr("action", "withdraw_proceeds"),
+            attr("to", treasury),
+            attr("amount", amount),
+        ],
+        ..Response::default()
+    };
+    Ok(res)
+}
+
+pub fn execute_update_treasury(
+    deps: DepsMut,
+    info: MessageInfo,
+    treasury: HumanAddr,
+) -> Result<Response, ContractError> {
+    let mut ico_info = ICO.load(deps.storage)?;
+    if deps.api.canonical_address(&info.sender)? != ico_info.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ico_info.treasury = deps.api.canonical_address(&treasury)?;
+    ICO.save(deps.storage, &ico_info)?;
+
+    let res = Response {
+        attributes: vec![attr("action", "update_treasury"), attr("treasury", treasury)],
+        ..Response::default()
+    };
+    Ok(res)
+}
+
+pub fn execute_update_attesters(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<Binary>,
+    remove: Vec<Binary>,
+) -> Result<Response, ContractError> {
+    let mut ico_info = ICO.load(deps.storage)?;
+    if deps.api.canonical_address(&info.sender)? != ico_info.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    //compressed or uncompressed SEC1 keys, the same formats secp256k1_verify takes
+    if add.iter().any(|pubkey| pubkey.as_slice().len() != 33 && pubkey.as_slice().len() != 65) {
+        return Err(ContractError::InvalidAttester {});
+    }
+    ico_info.attesters.retain(|pubkey| !remove.contains(pubkey));
+    for pubkey in add {
+        if !ico_info.attesters.contains(&pubkey) {
+            ico_info.attesters.push(pubkey);
+        }
+    }
+    ICO.save(deps.storage, &ico_info)?;
+
+    let res = Response {
+        attributes: vec![
+            attr("action", "update_attesters"),
+            attr("attesters", ico_info.attesters.len()),
+        ],
+        ..Response::default()
+    };
+    Ok(res)
+}
+
+pub fn execute_update_merkle_root(
+    deps: DepsMut,
+    info: MessageInfo,
+    merkle_root: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let mut ico_info = ICO.load(deps.storage)?;
+    if deps.api.canonical_address(&info.sender)? != ico_info.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if merkle_root.as_ref().map_or(false, |root| root.as_slice().len() != 32) {
+        return Err(ContractError::InvalidMerkleRoot {});
+    }
+
+    ico_info.merkle_root = merkle_root;
+    ICO.save(deps.storage, &ico_info)?;
+
+    let res = Response {
+        attributes: vec![
+            attr("action", "update_merkle_root"),
+            attr("merkle_root", ico_info.merkle_root.map_or(String::new(), |root| root.to_base64())),
+        ],
+        ..Response::default()
+    };
+    Ok(res)
+}
+
+pub fn execute_update_allowlist(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<AllowlistEntry>,
+    remove: Vec<HumanAddr>,
+) -> Result<Response, ContractError> {
+    let ico_info = ICO.load(deps.storage)?;
+    if deps.api.canonical_address(&info.sender)? != ico_info.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let added = add.len();
+    for entry in add {
+        let key = deps.api.canonical_address(&entry.address)?;
+        ALLOWLIST.save(deps.storage, key.as_slice(), &entry)?;
+    }
+    let removed = remove.len();
+    for address in remove {
+        let key = deps.api.canonical_address(&address)?;
+        ALLOWLIST.remove(deps.storage, key.as_slice());
+    }
+
+    let res = Response {
+        attributes: vec![
+            attr("action", "update_allowlist"),
+            attr("added", added),
+            attr("removed", removed),
+        ],
+        ..Response::default()
+    };
+    Ok(res)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    //already on the map layout, nothing to move
+    if TOTALS.may_load(deps.storage)?.is_some() {
+        return Ok(Response::default());
+    }
+
+    let legacy = LEGACY_ICO.load(deps.storage)?;
+    let denom = legacy.fundraise_denom;
+    let ico_info = ICOInfo {
+        fundraise_goal: legacy.fundraise_goal,
+        base_conv_ratio: legacy.base_conv_ratio,
+        owner: legacy.owner.clone(),
+        fundraising_open: legacy.fundraising_open,
+        fundraise_asset: FundraiseAsset::Native {
+            denom: denom.clone(),
+        },
+        accepted_denoms: vec![],
+        oracle: None,
+        rates: legacy.rates,
+        tier_mode: TierMode::Whole,
+        start: None,
+        end: Expiration::Never {},
+        soft_cap: false,
+        hard_cap: None,
+        treasury: legacy.owner,
+        withdrawn: Uint128(0),
+        vesting: None,
+        closed_at: None,
+        tokens_for_sale: None,
+        min_contribution: None,
+        max_contribution: None,
+        allowlisted: false,
+        merkle_root: None,
+        kyc_required: false,
+        attesters: vec![],
+        dutch_auction: None,
+        batch_auction: None,
+        batch_clearing: None,
+        commit_reveal: None,
+        bonding_curve: None,
+    };
+
+    let mut totals = Totals {
+        fundraise_bal: legacy.fundraise_bal,
+        deposits: NativeBalance(vec![coin(legacy.fundraise_bal.u128(), denom.clone())]),
+        ..Totals::default()
+    };
+    for f in legacy.fundraisers {
+        let allocation = ico_info.allocation_for(f.balance);
+        //closed legacy sales already pushed everyone their tokens
+        let claimed = if ico_info.fundraising_open {
+            Uint128(0)
+        } else {
+            allocation
+        };
+        let key = deps.api.canonical_address(&f.source)?;
+        FUNDRAISERS.save(
+            deps.storage,
+            key.as_slice(),
+            &Fundraiser {
+                source: f.source,
+                balance: f.balance,
+                refunded: Uint128(0),
+                allocation,
+                claimed,
+                deposits: NativeBalance(vec![coin(f.balance.u128(), denom.clone())]),
+            },
+        )?;
+        totals.fundraisers += 1;
+        totals.allocated += allocation;
+    }
+
+    ICO.save(deps.storage, &ico_info)?;
+    TOTALS.save(deps.storage, &totals)?;
+
+    let res = Response {
+        attributes: vec![attr("action", "migrate"), attr("fundraisers", totals.fundraisers)],
+        ..Response::default()
+    };
+    Ok(res)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::TokenInfo {} => to_binary(&query_token_info(deps)?),
+        QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
+        QueryMsg::Allowance { owner, spender } => {
+            to_binary(&query_allowance(deps, owner, spender)?)
+        }
+        QueryMsg::AllAllowances {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_all_allowances(deps, owner, start_after, limit)?),
+        QueryMsg::AllAccounts { start_after, limit } => {
+            to_binary(&query_all_accounts(deps, start_after, limit)?)
+        }
+        QueryMsg::FundraiseInfo {} => to_binary(&query_fundraise(deps, env)?),
+        QueryMsg::StakedInfo { start_after, limit } => {
+            to_binary(&query_staked(deps, start_after, limit)?)
+        }
+        QueryMsg::Contribution { address } => to_binary(&query_contribution(deps, address)?),
+        QueryMsg::SimulateContribution {
+            address,
+            amount,
+            allocation,
+            attestation,
+        } => to_binary(&query_simulate_contribution(
+            deps,
+            env,
+            address,
+            amount,
+            allocation,
+            attestation,
+        )?),
+        QueryMsg::Claimable { address } => to_binary(&query_claimable(deps, env, address)?),
+        QueryMsg::VestingStatus { address } => {
+            to_binary(&query_vesting_status(deps, env, address)?)
+        }
+        QueryMsg::Reserves {} => to_binary(&query_reserves(deps)?),
+        QueryMsg::CurrentPrice {} => to_binary(&query_current_price(deps, env)?),
+        QueryMsg::Bids { start_after, limit } => to_binary(&query_bids(deps, start_after, limit)?),
+        QueryMsg::BatchClearing {} => to_binary(&query_batch_clearing(deps)?),
+        QueryMsg::Commitment { address } => to_binary(&query_commitment(deps, address)?),
+        QueryMsg::SimulateBuy { amount } => to_binary(&query_simulate_buy(deps, amount)?),
+        QueryMsg::SimulateSell { amount } => to_binary(&query_simulate_sell(deps, amount)?),
+        QueryMsg::Allowlist { start_after, limit } => {
+            to_binary(&query_allowlist(deps, start_after, limit)?)
+        }
+
+    }
+}
+
+pub fn query_fundraise(deps: Deps, env: Env) -> StdResult<FundraiseInfoResponse> {
+    let ico_info = ICO.load(deps.storage)?;
+    let totals = TOTALS.load(deps.storage)?;
+    let status = sale_status(&ico_info, &totals, &env.block);
+    let proceeds_remaining = ico_info.proceeds_remaining(&totals);
+    let clearing_price = ico_info.clearing_price();
 
     let res = FundraiseInfoResponse {
         fundraise_goal: ico_info.fundraise_goal,
-        fundraise_bal: ico_info.fundraise_bal,
+        fundraise_bal: totals.fundraise_bal,
         base_conv_ratio: ico_info.base_conv_ratio,
         owner: deps.api.human_address(&ico_info.owner)?,
         fundraising_open: ico_info.fundraising_open,
-        fundraise_denom: ico_info.fundraise_denom,
+        fundraise_asset: ico_info.fundraise_asset,
+        accepted_denoms: ico_info.accepted_denoms,
+        oracle: ico_info.oracle,
+        deposits: totals.deposits.clone().into_vec(),
         rates: ico_info.rates,
+        tier_mode: ico_info.tier_mode,
+        start: ico_info.start,
+        end: ico_info.end,
+        soft_cap: ico_info.soft_cap,
+        hard_cap: ico_info.hard_cap,
+        treasury: deps.api.human_address(&ico_info.treasury)?,
+        withdrawn: ico_info.withdrawn,
+        proceeds_remaining,
+        vesting: ico_info.vesting,
+        closed_at: ico_info.closed_at,
+        max_supply: TOKEN_INFO.load(deps.storage)?.get_cap(),
+        tokens_for_sale: ico_info.tokens_for_sale,
+        min_contribution: ico_info.min_contribution,
+        max_contribution: ico_info.max_contribution,
+        allowlisted: ico_info.allowlisted,
+        merkle_root: ico_info.merkle_root,
+        kyc_required: ico_info.kyc_required,
+        attesters: ico_info.attesters,
+        clearing_price,
+        dutch_auction: ico_info.dutch_auction,
+        batch_auction: ico_info.batch_auction,
+        commit_phase: ico_info
+            .commit_reveal
+            .map(|commit_reveal| commit_phase(&commit_reveal, &env.block)),
+        commit_reveal: ico_info.commit_reveal,
+        escrowed: totals.escrowed,
+        bonding_curve: ico_info.bonding_curve,
+        curve_supply: totals.curve_supply,
+        status,
     };
     Ok(res)
 }
 
-pub fn query_staked(deps: Deps) -> StdResult<ListResponse> {
-    let ico_info = ICO.load(deps.storage)?;
+pub fn query_staked(
+    deps: Deps,
+    start_after: Option<HumanAddr>,
+    limit: Option<u32>,
+) -> StdResult<ListResponse> {
+    let totals = TOTALS.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start_human(deps.api, start_after)?.map(Bound::exclusive);
+
+    let fundraisers: StdResult<Vec<Fundraiser>> = FUNDRAISERS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, f)| f))
+        .collect();
 
     let res = ListResponse {
-        total_staked: ico_info.fundraise_bal,
-        fundraisers: ico_info.fundraisers, 
+        total_staked: totals.fundraise_bal,
+        fundraisers: fundraisers?,
     };
     Ok(res)
 }
 
+pub fn query_allowlist(
+    deps: Deps,
+    start_after: Option<HumanAddr>,
+    limit: Option<u32>,
+) -> StdResult<AllowlistResponse> {
+    let ico_info = ICO.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start_human(deps.api, start_after)?.map(Bound::exclusive);
 
-#[cfg(test)]
-mod tests {
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
-    use cosmwasm_std::{coins, CosmosMsg, Uint128, Decimal};
-    use cw20::{TokenInfoResponse, BalanceResponse};
-    use crate::msg::RateInit;
+    let entries: StdResult<Vec<AllowlistEntry>> = ALLOWLIST
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, entry)| entry))
+        .collect();
 
-    use super::*;
+    let res = AllowlistResponse {
+        allowlisted: ico_info.allowlisted,
+        entries: entries?,
+    };
+    Ok(res)
+}
 
-    #[test]
-    fn can_add_luna() {
-        let mut deps = mock_dependencies(&[]);
+pub fn query_contribution(deps: Deps, address: HumanAddr) -> StdResult<ContributionResponse> {
+    let ico_info = ICO.load(deps.storage)?;
+    let key = deps.api.canonical_address(&address)?;
 
-        // instantiate a contract
-        let instantiate_msg = InstantiateMsg {
-            fundraise_goal: Uint128(100),
-            base_conv_ratio_num: Uint128(1),
-            base_conv_ratio_den: Uint128(1),
-            fundraise_denom: "uluna".to_string(),
-            name: "Shark Coin".to_string(),
-            symbol: "ushark".to_string(),
-            decimals: 0,
-            rates: None,
-        };
+    let (balance, deposits) = FUNDRAISERS
+        .may_load(deps.storage, key.as_slice())?
+        .map_or((Uint128(0), vec![]), |f| (f.balance, f.deposits.into_vec()));
 
-        let info = mock_info(&HumanAddr::from("god"), &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
-        assert_eq!(0, res.messages.len());
+    let res = ContributionResponse {
+        address,
+        rate: ico_info.rate_for(balance),
+        balance,
+        deposits,
+    };
+    Ok(res)
+}
 
-        //add funds
-        let sender = HumanAddr::from("casper");
-        let balance = coins(5, "uluna");
-        let info = mock_info(&sender, &balance);
-        let msg = ExecuteMsg::AddFunds {};
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(attr("action", "add_funds"), res.attributes[0]);
+pub fn query_simulate_contribution(
+    deps: Deps,
+    env: Env,
+    address: HumanAddr,
+    amount: Uint128,
+    allocation: Option<AllocationProof>,
+    attestation: Option<Attestation>,
+) -> StdResult<SimulationResponse> {
+    let ico_info = ICO.load(deps.storage)?;
+    let totals = TOTALS.load(deps.storage)?;
+    let key = deps.api.canonical_address(&address)?;
 
-        //add funds 2
-        let sender = HumanAddr::from("marcel");
-        let balance = coins(5, "uluna");
-        let info = mock_info(&sender, &balance);
-        let msg = ExecuteMsg::AddFunds {};
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(attr("action", "add_funds"), res.attributes[0]);
+    let (balance, prev_tokens) = FUNDRAISERS
+        .may_load(deps.storage, key.as_slice())?
+        .map_or((Uint128(0), Uint128(0)), |f| (f.balance, f.allocation));
 
-        let qfund = query_fundraise(deps.as_ref()).unwrap();
-        assert_eq!(
-            qfund,
-            FundraiseInfoResponse {
-                fundraise_goal: Uint128(100),
-                fundraise_bal: Uint128(10),
-                base_conv_ratio: Decimal::one(),
-                owner: HumanAddr::from("god"),
-                fundraising_open: true,
-                fundraise_denom: "uluna".to_string(),
-                rates: vec![Rate {min: Uint128(0), ratio: Decimal::one()}],
-            }
-        );
+    //run the same checks AddFunds would, but report a rejection instead of failing the query
+    let accepted = contribution_cap(deps.storage, &ico_info, &address, &key, allocation.as_ref())
+        .and_then(|max| accept_contribution(&ico_info, &totals, &env.block, balance, max, amount))
+        .and_then(|accepted| {
+            check_attestation(deps.api, &ico_info, &env, &address, balance + accepted, attestation.as_ref())?;
+            Ok(accepted)
+        });
+    let (accepted_amount, error) = match accepted {
+        Ok(accepted) => (accepted, None),
+        Err(err) => (Uint128(0), Some(err.to_string())),
+    };
 
-        //do the staking accounts exist
-        let qstaked = query_staked(deps.as_ref()).unwrap();
-        assert!(qstaked.fundraisers.iter().any(|f| f.balance == Uint128(5) && f.source == HumanAddr::from("casper")));
-        assert!(qstaked.fundraisers.iter().any(|f| f.balance == Uint128(5) && f.source == HumanAddr::from("marcel")));
+    let total_tokens = ico_info.allocation_after(&totals, balance, prev_tokens, accepted_amount, env.block.time);
+
+    let res = SimulationResponse {
+        accepted: error.is_none(),
+        error,
+        accepted_amount,
+        rate: ico_info.rate_for(balance + accepted_amount),
+        tokens: Uint128(total_tokens.u128() - prev_tokens.u128()),
+        total_tokens,
+    };
+    Ok(res)
+}
+
+pub fn query_claimable(deps: Deps, env: Env, address: HumanAddr) -> StdResult<ClaimableResponse> {
+    let ico_info = ICO.load(deps.storage)?;
+    let totals = TOTALS.load(deps.storage)?;
+    let key = deps.api.canonical_address(&address)?;
+
+    let res = match FUNDRAISERS.may_load(deps.storage, key.as_slice())? {
+        Some(f) if !ico_info.is_failed(&totals) => {
+            let allocation = totals.share_of(f.allocation);
+            //only what has vested so far can be claimed
+            let unlocked = match (ico_info.vesting, ico_info.closed_at) {
+                (Some(vesting), Some(closed_at)) => vesting.vested(allocation, closed_at, env.block.time),
+                (Some(_), None) => Uint128(0),
+                (None, _) => allocation,
+            };
+            ClaimableResponse {
+                allocation,
+                claimable: Uint128(unlocked.u128() - f.claimed.u128()),
+                claimed: !f.claimed.is_zero() && f.claimed == allocation,
+            }
+        }
+        _ => ClaimableResponse {
+            allocation: Uint128(0),
+            claimable: Uint128(0),
+            claimed: false,
+        },
+    };
+    Ok(res)
+}
+pub fn query_vesting_status(deps: Deps, env: Env, address: HumanAddr) -> StdResult<VestingStatusResponse> {
+    let ico_info = ICO.load(deps.storage)?;
+    let totals = TOTALS.load(deps.storage)?;
+    let key = deps.api.canonical_address(&address)?;
+
+    let (total, claimed) = match FUNDRAISERS.may_load(deps.storage, key.as_slice())? {
+        Some(f) if !ico_info.is_failed(&totals) => (totals.share_of(f.allocation), f.claimed),
+        _ => (Uint128(0), Uint128(0)),
+    };
+
+    let (vested, next_unlock) = match (ico_info.vesting, ico_info.closed_at) {
+        (Some(vesting), Some(closed_at)) => (
+            vesting.vested(total, closed_at, env.block.time),
+            vesting.next_unlock(closed_at, env.block.time),
+        ),
+        (None, Some(_)) => (total, None),
+        (_, None) => (Uint128(0), None),
+    };
+
+    let res = VestingStatusResponse {
+        total,
+        vested,
+        claimed,
+        next_unlock,
+    };
+    Ok(res)
+}
+
+pub fn query_current_price(deps: Deps, env: Env) -> StdResult<CurrentPriceResponse> {
+    let ico_info = ICO.load(deps.storage)?;
+    let auction = ico_info
+        .dutch_auction
+        .ok_or_else(|| StdError::generic_err("Sale is not a Dutch auction"))?;
+
+    let res = match ico_info.clearing_price() {
+        Some(price) => CurrentPriceResponse {
+            price,
+            floor_price: auction.floor_price,
+            settled: true,
+        },
+        None => CurrentPriceResponse {
+            price: auction.price_at(env.block.time),
+            floor_price: auction.floor_price,
+            settled: false,
+        },
+    };
+    Ok(res)
+}
+
+pub fn query_bids(deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<BidsResponse> {
+    let ico_info = ICO.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|id| Bound::exclusive(U64Key::from(id)));
+
+    let bids: Vec<Bid> = BIDS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, bid)| bid))
+        .collect::<StdResult<_>>()?;
+
+    let res = BidsResponse {
+        bids: bids
+            .into_iter()
+            .map(|bid| BidResponse {
+                tokens: ico_info
+                    .batch_clearing
+                    .as_ref()
+                    .map_or(Uint128(0), |clearing| clearing.fill(&bid)),
+                id: bid.id,
+                bidder: bid.bidder,
+                amount: bid.amount,
+                max_price: bid.max_price,
+            })
+            .collect(),
+    };
+    Ok(res)
+}
+
+pub fn query_batch_clearing(deps: Deps) -> StdResult<BatchClearingResponse> {
+    let ico_info = ICO.load(deps.storage)?;
+    let totals = TOTALS.load(deps.storage)?;
+    let auction = ico_info
+        .batch_auction
+        .ok_or_else(|| StdError::generic_err("Sale is not a batch auction"))?;
+
+    let res = BatchClearingResponse {
+        reserve_price: auction.reserve_price,
+        tokens_for_sale: ico_info.tokens_for_sale.unwrap_or_default(),
+        bids: totals.bids,
+        clearing: ico_info.batch_clearing,
+    };
+    Ok(res)
+}
+
+pub fn query_commitment(deps: Deps, address: HumanAddr) -> StdResult<CommitmentResponse> {
+    let key = deps.api.canonical_address(&address)?;
+    let res = CommitmentResponse {
+        commitment: COMMITMENTS.may_load(deps.storage, key.as_slice())?,
+    };
+    Ok(res)
+}
+
+pub fn query_simulate_buy(deps: Deps, amount: Uint128) -> StdResult<CurveSimulationResponse> {
+    let (curve, supply) = curve_and_supply(deps)?;
+    let (tokens, funds) = curve.buy(supply, amount).ok_or_else(beyond_curve)?;
+    curve_simulation(&curve, supply, supply + tokens, tokens, funds)
+}
+
+pub fn query_simulate_sell(deps: Deps, amount: Uint128) -> StdResult<CurveSimulationResponse> {
+    let (curve, supply) = curve_and_supply(deps)?;
+    let funds = curve.sell(supply, amount).ok_or_else(beyond_curve)?;
+    let after = Uint128(supply.u128() - amount.u128());
+    curve_simulation(&curve, supply, after, amount, funds)
+}
+
+fn curve_and_supply(deps: Deps) -> StdResult<(BondingCurve, Uint128)> {
+    let curve = ICO
+        .load(deps.storage)?
+        .bonding_curve
+        .ok_or_else(|| StdError::generic_err("Sale is not a bonding curve"))?;
+    Ok((curve, TOTALS.load(deps.storage)?.curve_supply))
+}
+
+fn curve_simulation(
+    curve: &BondingCurve,
+    from: Uint128,
+    to: Uint128,
+    tokens: Uint128,
+    funds: Uint128,
+) -> StdResult<CurveSimulationResponse> {
+    let quote = curve.quote(from, to, tokens, funds).ok_or_else(beyond_curve)?;
+    let res = CurveSimulationResponse {
+        tokens,
+        funds,
+        price_before: quote.price_before,
+        price_after: quote.price_after,
+        average_price: quote.average_price,
+        slippage: quote.slippage,
+    };
+    Ok(res)
+}
+
+fn beyond_curve() -> StdError {
+    StdError::generic_err(ContractError::BeyondCurve {}.to_string())
+}
+
+pub fn query_reserves(deps: Deps) -> StdResult<ReservesResponse> {
+    let reserves: Vec<Reserve> = RESERVES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, reserve)| reserve))
+        .collect::<StdResult<_>>()?;
+    let total_reserved = reserves
+        .iter()
+        .fold(Uint128(0), |total, reserve| total + reserve.allocation);
+
+    let res = ReservesResponse {
+        total_reserved,
+        reserves,
+    };
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+    use cosmwasm_std::{coins, CosmosMsg, Empty, Uint128, Decimal};
+    use cw20::{TokenInfoResponse, BalanceResponse};
+    use cw_multi_test::{App, Contract, ContractWrapper, SimpleBank};
+    use cw_storage_plus::Map;
+    use serde::{Deserialize, Serialize};
+    use k256::ecdsa::signature::DigestSigner;
+    use k256::ecdsa::{Signature, SigningKey, VerifyingKey};
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
+    use crate::curve::BondingCurve;
+    use crate::merkle::MerkleTree;
+    use crate::msg::{RateInit, ReserveInit};
+    use crate::state::{BatchAuction, CommitReveal, DutchAuction, LegacyFundraiser, LegacyICOInfo, ReserveAmount, VestingSchedule};
+
+    use super::*;
+
+    fn mock_instantiate_msg() -> InstantiateMsg {
+        InstantiateMsg {
+            fundraise_goal: Uint128(100),
+            base_conv_ratio_num: Uint128(1),
+            base_conv_ratio_den: Uint128(1),
+            fundraise_denom: "uluna".to_string(),
+            fundraise_cw20: None,
+            accepted_denoms: None,
+            oracle: None,
+            dutch_auction: None,
+            batch_auction: None,
+            commit_reveal: None,
+            bonding_curve: None,
+            name: "Shark Coin".to_string(),
+            symbol: "ushark".to_string(),
+            decimals: 0,
+            rates: None,
+            tier_mode: TierMode::Whole,
+            start: None,
+            end: None,
+            soft_cap: false,
+            hard_cap: None,
+            treasury: None,
+            vesting: None,
+            reserves: None,
+            max_supply: None,
+            tokens_for_sale: None,
+            min_contribution: None,
+            max_contribution: None,
+            allowlisted: false,
+            merkle_root: None,
+            kyc_required: false,
+        }
+    }
+
+    #[test]
+    fn can_add_luna() {
+        let mut deps = mock_dependencies(&[]);
+
+        // instantiate a contract
+        let instantiate_msg = mock_instantiate_msg();
+
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        //add funds
+        let sender = HumanAddr::from("casper");
+        let balance = coins(5, "uluna");
+        let info = mock_info(&sender, &balance);
+        let msg = ExecuteMsg::AddFunds { allocation: None, attestation: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(attr("action", "add_funds"), res.attributes[0]);
+
+        //add funds 2
+        let sender = HumanAddr::from("marcel");
+        let balance = coins(5, "uluna");
+        let info = mock_info(&sender, &balance);
+        let msg = ExecuteMsg::AddFunds { allocation: None, attestation: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(attr("action", "add_funds"), res.attributes[0]);
+
+        let qfund = query_fundraise(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(
+            qfund,
+            FundraiseInfoResponse {
+                fundraise_goal: Uint128(100),
+                fundraise_bal: Uint128(10),
+                base_conv_ratio: Decimal::one(),
+                owner: HumanAddr::from("god"),
+                fundraising_open: true,
+                fundraise_asset: FundraiseAsset::Native {
+                    denom: "uluna".to_string(),
+                },
+                accepted_denoms: vec![],
+                oracle: None,
+                deposits: coins(10, "uluna"),
+                rates: vec![Rate {min: Uint128(0), ratio: Decimal::one()}],
+                tier_mode: TierMode::Whole,
+                start: None,
+                end: Expiration::Never {},
+                soft_cap: false,
+                hard_cap: None,
+                treasury: HumanAddr::from("god"),
+                withdrawn: Uint128(0),
+                proceeds_remaining: Uint128(10),
+                vesting: None,
+                closed_at: None,
+                max_supply: None,
+                tokens_for_sale: None,
+                min_contribution: None,
+                max_contribution: None,
+                allowlisted: false,
+                merkle_root: None,
+                kyc_required: false,
+                attesters: vec![],
+                dutch_auction: None,
+                batch_auction: None,
+                commit_reveal: None,
+                commit_phase: None,
+                escrowed: Uint128(0),
+                bonding_curve: None,
+                curve_supply: Uint128(0),
+                clearing_price: None,
+                status: SaleStatus::Open,
+            }
+        );
+
+        //do the staking accounts exist
+        let qstaked = query_staked(deps.as_ref(), None, None).unwrap();
+        assert!(qstaked.fundraisers.iter().any(|f| f.balance == Uint128(5) && f.source == HumanAddr::from("casper")));
+        assert!(qstaked.fundraisers.iter().any(|f| f.balance == Uint128(5) && f.source == HumanAddr::from("marcel")));
+    }
+
+    #[test]
+    fn token_created() {
+        let mut deps = mock_dependencies(&[]);
+
+        // instantiate a contract
+        let instantiate_msg = mock_instantiate_msg();
+
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        //check if token exists
+        let qtoken = query_token_info(deps.as_ref()).unwrap();
+        assert_eq!(
+            qtoken,
+            TokenInfoResponse {
+                name: "Shark Coin".to_string(),
+                symbol: "ushark".to_string(),
+                decimals: 0,
+                total_supply: Uint128(0),
+            }
+        );
+    }
+
+    #[test]
+    fn close_and_send() {
+        let mut deps = mock_dependencies(&[]);
+
+        // instantiate a contract
+        let instantiate_msg = mock_instantiate_msg();
+
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        //add funds
+        let sender = HumanAddr::from("casper");
+        let balance = coins(100, "uluna");
+        let info = mock_info(&sender, &balance);
+        let msg = ExecuteMsg::AddFunds { allocation: None, attestation: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(attr("action", "add_funds"), res.attributes[0]);
+
+        //add funds
+        let sender = HumanAddr::from("marcel");
+        let balance = coins(50, "uluna");
+        let info = mock_info(&sender, &balance);
+        let msg = ExecuteMsg::AddFunds { allocation: None, attestation: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(attr("action", "add_funds"), res.attributes[0]);
+
+        //close fundraise
+        let sender = HumanAddr::from("casper");
+        let info = mock_info(&sender, &[]);
+        let msg = ExecuteMsg::CloseFundraise {};
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(attr("action", "close_fundraise"), res.attributes[0]);
+        
+        let sendmsg = &res.messages[0];
+        match sendmsg {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg:_, send }) => {
+                assert_eq!(send, &[]);
+                assert_eq!(contract_addr, &HumanAddr::from(MOCK_CONTRACT_ADDR));
+            }
+            _ => panic!("Unexpected message: {:?}", sendmsg),
+        }
+
+        //fake callback
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let msg = ExecuteMsg::_SendTokens {};
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        //check if 150 token was minted
+        let qtoken = query_token_info(deps.as_ref()).unwrap();
+        assert_eq!(
+            qtoken,
+            TokenInfoResponse {
+                name: "Shark Coin".to_string(),
+                symbol: "ushark".to_string(),
+                decimals: 0,
+                total_supply: Uint128(150),
+            }
+        );
+
+        //check to see if contract was given minted coins
+        let qbal = query_balance(deps.as_ref(), HumanAddr::from(MOCK_CONTRACT_ADDR)).unwrap();
+        assert_eq!(
+            qbal,
+            BalanceResponse {
+                balance: Uint128(150)
+            }
+        );
+
+        //nothing is pushed, fundraisers claim instead
+        assert_eq!(0, res.messages.len());
+        assert_eq!(attr("action", "send_tokens"), res.attributes[0]);
+
+        let qclaim = query_claimable(deps.as_ref(), mock_env(), HumanAddr::from("casper")).unwrap();
+        assert_eq!(
+            qclaim,
+            ClaimableResponse {
+                allocation: Uint128(100),
+                claimable: Uint128(100),
+                claimed: false,
+            }
+        );
+
+        //claims
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::ClaimTokens {}).unwrap();
+        assert_eq!(attr("amount", "100"), res.attributes[2]);
+
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimTokens {}).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyClaimed {});
+
+        let info = mock_info(&HumanAddr::from("marcel"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimTokens {}).unwrap();
+
+        let qclaim = query_claimable(deps.as_ref(), mock_env(), HumanAddr::from("casper")).unwrap();
+        assert_eq!(
+            qclaim,
+            ClaimableResponse {
+                allocation: Uint128(100),
+                claimable: Uint128(0),
+                claimed: true,
+            }
+        );
+
+        let qbal = query_balance(deps.as_ref(), HumanAddr::from(MOCK_CONTRACT_ADDR)).unwrap();
+        assert_eq!(qbal.balance, Uint128(0));
+
+        //check their balance
+        let qbal = query_balance(deps.as_ref(), HumanAddr::from("casper")).unwrap();
+        assert_eq!(
+            qbal,
+            BalanceResponse {
+                balance: Uint128(100)
+            }
+        );
+
+        let qbal = query_balance(deps.as_ref(), HumanAddr::from("marcel")).unwrap();
+        assert_eq!(
+            qbal,
+            BalanceResponse {
+                balance: Uint128(50)
+            }
+        );
+
+    }
+
+    #[test]
+    fn rate_sort() {
+        let mut rate_arr:Vec<Rate> = vec![];
+
+        let r1 = Rate {min: Uint128(5), ratio: Decimal::percent(120)};
+        let r2 = Rate {min: Uint128(10), ratio: Decimal::percent(200)};
+
+        rate_arr.push(r1);
+        rate_arr.push(r2);
+
+        assert_eq!(rate_arr[0], Rate {min: Uint128(5), ratio: Decimal::percent(120)});
+
+        rate_arr.sort_by(|a, b| b.min.cmp(&a.min));
+
+        assert_eq!(rate_arr[0], Rate {min: Uint128(10), ratio: Decimal::percent(200)});
+    }
+
+    #[test]
+    fn recieve_proper_rate() {
+        let mut deps = mock_dependencies(&[]);
+
+        // instantiate a contract
+        let instantiate_msg = InstantiateMsg {
+            fundraise_goal: Uint128(10),
+            rates: Some(vec![RateInit {min: Uint128(10), ratio_num: Uint128(1), ratio_den: Uint128(2)}, 
+                             RateInit {min: Uint128(20), ratio_num: Uint128(1), ratio_den: Uint128(3)}]),
+            ..mock_instantiate_msg()
+        };
+
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        //add funds
+        let sender = HumanAddr::from("casper");
+        let balance = coins(5, "uluna");
+        let info = mock_info(&sender, &balance);
+        let msg = ExecuteMsg::AddFunds { allocation: None, attestation: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(attr("action", "add_funds"), res.attributes[0]);
+
+        //add funds
+        let sender = HumanAddr::from("marcel");
+        let balance = coins(10, "uluna");
+        let info = mock_info(&sender, &balance);
+        let msg = ExecuteMsg::AddFunds { allocation: None, attestation: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(attr("action", "add_funds"), res.attributes[0]);
+
+        //add funds
+        let sender = HumanAddr::from("kanye");
+        let balance = coins(50, "uluna");
+        let info = mock_info(&sender, &balance);
+        let msg = ExecuteMsg::AddFunds { allocation: None, attestation: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(attr("action", "add_funds"), res.attributes[0]);
+
+        //close fundraise
+        let sender = HumanAddr::from("casper");
+        let info = mock_info(&sender, &[]);
+        let msg = ExecuteMsg::CloseFundraise {};
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(attr("action", "close_fundraise"), res.attributes[0]);
+        
+        let sendmsg = &res.messages[0];
+        match sendmsg {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg:_, send }) => {
+                assert_eq!(send, &[]);
+                assert_eq!(contract_addr, &HumanAddr::from(MOCK_CONTRACT_ADDR));
+            }
+            _ => panic!("Unexpected message: {:?}", sendmsg),
+        }
+
+        //fake callback
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let msg = ExecuteMsg::_SendTokens {};
+        let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        //check if 5 + 10*2 + 50*3 = 175 tokens were minted
+        let qtoken = query_token_info(deps.as_ref()).unwrap();
+        assert_eq!(
+            qtoken,
+            TokenInfoResponse {
+                name: "Shark Coin".to_string(),
+                symbol: "ushark".to_string(),
+                decimals: 0,
+                total_supply: Uint128(175),
+            }
+        );
+    }
+
+    #[test]
+    fn marginal_tiers() {
+        let mut deps = mock_dependencies(&[]);
+
+        //1 token per uluna up to 10, 2 from 10 to 20 and 3 above 20, each only on the part in its bracket
+        let instantiate_msg = InstantiateMsg {
+            rates: Some(vec![RateInit {min: Uint128(10), ratio_num: Uint128(1), ratio_den: Uint128(2)},
+                             RateInit {min: Uint128(20), ratio_num: Uint128(1), ratio_den: Uint128(3)}]),
+            tier_mode: TierMode::Marginal,
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        //crossing a tier only reprices what's above it, so there's no cliff at 20
+        for (amount, tokens) in &[(19, 28), (20, 30), (21, 33)] {
+            let qsim = query_simulate_contribution(deps.as_ref(), mock_env(), HumanAddr::from("lana"), Uint128(*amount), None, None).unwrap();
+            assert_eq!(qsim.tokens, Uint128(*tokens));
+        }
+
+        for (name, amount) in &[("casper", 5), ("marcel", 10), ("kanye", 50)] {
+            let info = mock_info(&HumanAddr::from(*name), &coins(*amount, "uluna"));
+            let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+        }
+
+        //topping up prices the new tranche exactly as the simulation says
+        let qsim = query_simulate_contribution(deps.as_ref(), mock_env(), HumanAddr::from("casper"), Uint128(10), None, None).unwrap();
+        assert_eq!((qsim.tokens, qsim.total_tokens), (Uint128(15), Uint128(20)));
+        let info = mock_info(&HumanAddr::from("casper"), &coins(10, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CloseFundraise {}).unwrap();
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::_SendTokens {}).unwrap();
+
+        //casper 10 + 5*2, marcel 10, kanye 10 + 10*2 + 30*3
+        for (name, tokens) in &[("casper", 20), ("marcel", 10), ("kanye", 120)] {
+            let qclaim = query_claimable(deps.as_ref(), mock_env(), HumanAddr::from(*name)).unwrap();
+            assert_eq!(qclaim.allocation, Uint128(*tokens));
+        }
+        assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, Uint128(150));
+    }
+
+    #[test]
+    fn sale_window() {
+        let mut deps = mock_dependencies(&[]);
+
+        // sale runs from height 100 until height 200
+        let instantiate_msg = InstantiateMsg {
+            start: Some(Expiration::AtHeight(100)),
+            end: Some(Expiration::AtHeight(200)),
+            ..mock_instantiate_msg()
+        };
+
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let qfund = query_fundraise(deps.as_ref(), env.clone()).unwrap();
+        assert_eq!(qfund.status, SaleStatus::Pending);
+
+        //too early
+        let info = mock_info(&HumanAddr::from("casper"), &coins(5, "uluna"));
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap_err();
+        assert_eq!(err, ContractError::FundraiseNotStarted {});
+
+        //open
+        env.block.height = 150;
+        let qfund = query_fundraise(deps.as_ref(), env.clone()).unwrap();
+        assert_eq!(qfund.status, SaleStatus::Open);
+        let _ = execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        //goal not met, so a random sender cannot close yet
+        let closer = mock_info(&HumanAddr::from("marcel"), &[]);
+        let err = execute(deps.as_mut(), env.clone(), closer.clone(), ExecuteMsg::CloseFundraise {}).unwrap_err();
+        assert_eq!(err, ContractError::FundraiseClosed {});
+
+        //too late
+        env.block.height = 200;
+        let qfund = query_fundraise(deps.as_ref(), env.clone()).unwrap();
+        assert_eq!(qfund.status, SaleStatus::Ended);
+        let err = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap_err();
+        assert_eq!(err, ContractError::FundraiseEnded {});
+
+        //anyone can close once the end has passed
+        let res = execute(deps.as_mut(), env.clone(), closer, ExecuteMsg::CloseFundraise {}).unwrap();
+        assert_eq!(attr("action", "close_fundraise"), res.attributes[0]);
+        let qfund = query_fundraise(deps.as_ref(), env).unwrap();
+        assert_eq!(qfund.status, SaleStatus::Closed);
+    }
+
+    #[test]
+    fn invalid_sale_window() {
+        let mut deps = mock_dependencies(&[]);
+
+        let instantiate_msg = InstantiateMsg {
+            start: Some(Expiration::AtHeight(200)),
+            end: Some(Expiration::AtHeight(100)),
+            ..mock_instantiate_msg()
+        };
+
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let err = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSaleWindow {});
+
+        //a height can't be ordered against a time
+        let instantiate_msg = InstantiateMsg {
+            start: Some(Expiration::AtTime(2_000_000_000)),
+            end: Some(Expiration::AtHeight(100)),
+            ..mock_instantiate_msg()
+        };
+
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let err = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSaleWindow {});
+    }
+
+    #[test]
+    fn soft_cap_refunds() {
+        let mut deps = mock_dependencies(&[]);
+
+        let instantiate_msg = InstantiateMsg {
+            soft_cap: true,
+            ..mock_instantiate_msg()
+        };
+
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        //add funds
+        let info = mock_info(&HumanAddr::from("casper"), &coins(40, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        //no refunds while the sale is open
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimRefund {}).unwrap_err();
+        assert_eq!(err, ContractError::RefundsUnavailable {});
+
+        //owner closes below the goal
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CloseFundraise {}).unwrap();
+        let qfund = query_fundraise(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(qfund.status, SaleStatus::Failed);
+
+        //callback mints nothing
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::_SendTokens {}).unwrap();
+        assert_eq!(0, res.messages.len());
+        let qtoken = query_token_info(deps.as_ref()).unwrap();
+        assert_eq!(qtoken.total_supply, Uint128(0));
+
+        //casper gets exactly what they sent
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::ClaimRefund {}).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: HumanAddr::from("casper"),
+                amount: coins(40, "uluna"),
+            })]
+        );
+
+        //but only once
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimRefund {}).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyRefunded {});
+
+        //marcel never contributed
+        let info = mock_info(&HumanAddr::from("marcel"), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimRefund {}).unwrap_err();
+        assert_eq!(err, ContractError::NoContribution {});
+    }
+
+    #[test]
+    fn hard_cap_returns_excess() {
+        let mut deps = mock_dependencies(&[]);
+
+        let instantiate_msg = InstantiateMsg {
+            hard_cap: Some(Uint128(150)),
+            ..mock_instantiate_msg()
+        };
+
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        //fits under the cap
+        let info = mock_info(&HumanAddr::from("casper"), &coins(100, "uluna"));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(attr("returned", "0"), res.attributes[3]);
+
+        //whale only gets the remaining 50 in
+        let info = mock_info(&HumanAddr::from("marcel"), &coins(500, "uluna"));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+        assert_eq!(attr("amount", "50"), res.attributes[2]);
+        assert_eq!(attr("returned", "450"), res.attributes[3]);
+        assert_eq!(attr("closed", "true"), res.attributes[4]);
+        assert_eq!(2, res.messages.len());
+        assert_eq!(
+            res.messages[0],
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: HumanAddr::from("marcel"),
+                amount: coins(450, "uluna"),
+            })
+        );
+        match &res.messages[1] {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg:_, send }) => {
+                assert_eq!(send, &[]);
+                assert_eq!(contract_addr, &HumanAddr::from(MOCK_CONTRACT_ADDR));
+            }
+            m => panic!("Unexpected message: {:?}", m),
+        }
+
+        let qfund = query_fundraise(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(qfund.fundraise_bal, Uint128(150));
+        assert_eq!(qfund.status, SaleStatus::Closed);
+
+        //sale is closed now
+        let info = mock_info(&HumanAddr::from("kanye"), &coins(1, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap_err();
+        assert_eq!(err, ContractError::FundraiseClosed {});
+    }
+
+    #[test]
+    fn withdraw_proceeds() {
+        let mut deps = mock_dependencies(&[]);
+
+        let instantiate_msg = InstantiateMsg {
+            treasury: Some(HumanAddr::from("vault")),
+            ..mock_instantiate_msg()
+        };
+
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        let info = mock_info(&HumanAddr::from("casper"), &coins(120, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        //nothing to withdraw while the sale is open
+        let owner = mock_info(&HumanAddr::from("god"), &[]);
+        let msg = ExecuteMsg::WithdrawProceeds { amount: None };
+        let err = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::ProceedsUnavailable {});
+
+        let _ = execute(deps.as_mut(), mock_env(), owner.clone(), ExecuteMsg::CloseFundraise {}).unwrap();
+
+        //only the owner can withdraw
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let msg = ExecuteMsg::WithdrawProceeds { amount: None };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        //partial withdrawal
+        let msg = ExecuteMsg::WithdrawProceeds { amount: Some(Uint128(20)) };
+        let res = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: HumanAddr::from("vault"),
+                amount: coins(20, "uluna"),
+            })]
+        );
+
+        let msg = ExecuteMsg::WithdrawProceeds { amount: Some(Uint128(101)) };
+        let err = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InsufficientProceeds {});
+
+        //rest goes to the new treasury
+        let msg = ExecuteMsg::UpdateTreasury { treasury: HumanAddr::from("vault2") };
+        let _ = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+        let msg = ExecuteMsg::WithdrawProceeds { amount: None };
+        let res = execute(deps.as_mut(), mock_env(), owner, msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: HumanAddr::from("vault2"),
+                amount: coins(100, "uluna"),
+            })]
+        );
+
+        let qfund = query_fundraise(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(qfund.withdrawn, Uint128(120));
+        assert_eq!(qfund.proceeds_remaining, Uint128(0));
+    }
+
+    #[test]
+    fn claim_before_close() {
+        let mut deps = mock_dependencies(&[]);
+
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let info = mock_info(&HumanAddr::from("casper"), &coins(10, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimTokens {}).unwrap_err();
+        assert_eq!(err, ContractError::ClaimsUnavailable {});
+
+        //close, but nobody else can claim
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CloseFundraise {}).unwrap();
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::_SendTokens {}).unwrap();
+
+        let info = mock_info(&HumanAddr::from("marcel"), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimTokens {}).unwrap_err();
+        assert_eq!(err, ContractError::NoContribution {});
+    }
+
+    #[test]
+    fn migrate_legacy_state() {
+        let mut deps = mock_dependencies(&[]);
+        let owner = deps.api.canonical_address(&HumanAddr::from("god")).unwrap();
+
+        //state as written by the Vec based contract
+        let legacy = LegacyICOInfo {
+            fundraise_goal: Uint128(100),
+            fundraise_bal: Uint128(30),
+            base_conv_ratio: Decimal::one(),
+            owner: owner.clone(),
+            fundraising_open: true,
+            fundraise_denom: "uluna".to_string(),
+            fundraisers: vec![
+                LegacyFundraiser { source: HumanAddr::from("casper"), balance: Uint128(10) },
+                LegacyFundraiser { source: HumanAddr::from("marcel"), balance: Uint128(20) },
+            ],
+            rates: vec![Rate {min: Uint128(0), ratio: Decimal::one()}],
+        };
+        LEGACY_ICO.save(&mut deps.storage, &legacy).unwrap();
+
+        //the legacy contract already minted through cw20-base
+        let token = TokenInfo {
+            name: "Shark Coin".to_string(),
+            symbol: "ushark".to_string(),
+            decimals: 0,
+            total_supply: Uint128(0),
+            mint: Some(MinterData {
+                minter: deps.api.canonical_address(&HumanAddr::from(MOCK_CONTRACT_ADDR)).unwrap(),
+                cap: None,
+            }),
+        };
+        TOKEN_INFO.save(&mut deps.storage, &token).unwrap();
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(attr("fundraisers", "2"), res.attributes[1]);
+
+        let totals = TOTALS.load(&deps.storage).unwrap();
+        assert_eq!(
+            totals,
+            Totals {
+                fundraise_bal: Uint128(30),
+                fundraisers: 2,
+                allocated: Uint128(30),
+                distributed: None,
+                deposits: NativeBalance(coins(30, "uluna")),
+                bids: 0,
+                escrowed: Uint128(0),
+                curve_supply: Uint128(0),
+            }
+        );
+
+        let qfund = query_fundraise(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(qfund.fundraise_bal, Uint128(30));
+        assert_eq!(qfund.owner, HumanAddr::from("god"));
+        assert_eq!(qfund.status, SaleStatus::Open);
+
+        //sale keeps working on the new layout
+        let info = mock_info(&HumanAddr::from("casper"), &coins(5, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+        let qstaked = query_staked(deps.as_ref(), None, None).unwrap();
+        assert_eq!(qstaked.total_staked, Uint128(35));
+        assert!(qstaked.fundraisers.iter().any(|f| f.balance == Uint128(15) && f.source == HumanAddr::from("casper")));
+
+        //migrating again is a no-op
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(0, res.attributes.len());
+    }
+
+    #[test]
+    fn paginate_contributors() {
+        let mut deps = mock_dependencies(&[]);
+
+        let instantiate_msg = InstantiateMsg {
+            rates: Some(vec![RateInit {min: Uint128(10), ratio_num: Uint128(1), ratio_den: Uint128(2)}]),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        for (i, name) in ["casper", "marcel", "kanye", "bianca", "dorian"].iter().enumerate() {
+            let info = mock_info(&HumanAddr::from(*name), &coins(5 * (i as u128 + 1), "uluna"));
+            let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+        }
+
+        //walk every page, everyone shows up exactly once
+        let mut seen = vec![];
+        let mut start_after = None;
+        loop {
+            let page = query_staked(deps.as_ref(), start_after, Some(2)).unwrap();
+            assert!(page.fundraisers.len() <= 2);
+            assert_eq!(page.total_staked, Uint128(75));
+            match page.fundraisers.last() {
+                Some(last) => start_after = Some(last.source.clone()),
+                None => break,
+            }
+            seen.extend(page.fundraisers.into_iter().map(|f| f.source));
+        }
+        assert_eq!(seen.len(), 5);
+
+        //same order every time
+        let all = query_staked(deps.as_ref(), None, None).unwrap();
+        assert_eq!(all.fundraisers.into_iter().map(|f| f.source).collect::<Vec<_>>(), seen);
+
+        //limit is capped
+        let all = query_staked(deps.as_ref(), None, Some(1000)).unwrap();
+        assert_eq!(all.fundraisers.len(), 5);
+
+        //single contributor with their tier
+        let qcontrib = query_contribution(deps.as_ref(), HumanAddr::from("marcel")).unwrap();
+        assert_eq!(
+            qcontrib,
+            ContributionResponse {
+                address: HumanAddr::from("marcel"),
+                balance: Uint128(10),
+                deposits: coins(10, "uluna"),
+                rate: Rate {min: Uint128(10), ratio: Decimal::percent(200)},
+            }
+        );
+
+        let qcontrib = query_contribution(deps.as_ref(), HumanAddr::from("nobody")).unwrap();
+        assert_eq!(qcontrib.balance, Uint128(0));
+        assert_eq!(qcontrib.rate, Rate {min: Uint128(0), ratio: Decimal::one()});
+    }
+
+    #[test]
+    fn simulate_contribution() {
+        let mut deps = mock_dependencies(&[]);
+
+        let instantiate_msg = InstantiateMsg {
+            fundraise_goal: Uint128(10),
+            hard_cap: Some(Uint128(40)),
+            rates: Some(vec![RateInit {min: Uint128(10), ratio_num: Uint128(1), ratio_den: Uint128(2)}, 
+                             RateInit {min: Uint128(20), ratio_num: Uint128(1), ratio_den: Uint128(3)}]),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        let info = mock_info(&HumanAddr::from("casper"), &coins(8, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        //8 -> 12 moves casper into the 2x tier for their whole balance
+        let sim = query_simulate_contribution(deps.as_ref(), mock_env(), HumanAddr::from("casper"), Uint128(4), None, None).unwrap();
+        assert_eq!(
+            sim,
+            SimulationResponse {
+                accepted: true,
+                error: None,
+                accepted_amount: Uint128(4),
+                rate: Rate {min: Uint128(10), ratio: Decimal::percent(200)},
+                tokens: Uint128(16),
+                total_tokens: Uint128(24),
+            }
+        );
+
+        //matches what AddFunds then does at close
+        let info = mock_info(&HumanAddr::from("casper"), &coins(4, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+        let qclaim = query_claimable(deps.as_ref(), mock_env(), HumanAddr::from("casper")).unwrap();
+        assert_eq!(qclaim.allocation, sim.total_tokens);
+
+        //only 28 more fits under the hard cap
+        let sim = query_simulate_contribution(deps.as_ref(), mock_env(), HumanAddr::from("marcel"), Uint128(50), None, None).unwrap();
+        assert!(sim.accepted);
+        assert_eq!(sim.accepted_amount, Uint128(28));
+        assert_eq!(sim.total_tokens, Uint128(84));
+
+        //closed sale rejects
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CloseFundraise {}).unwrap();
+        let sim = query_simulate_contribution(deps.as_ref(), mock_env(), HumanAddr::from("marcel"), Uint128(5), None, None).unwrap();
+        assert!(!sim.accepted);
+        assert_eq!(sim.error, Some(ContractError::FundraiseClosed {}.to_string()));
+        assert_eq!(sim.tokens, Uint128(0));
+    }
+
+    #[test]
+    fn cw20_surface() {
+        let mut deps = mock_dependencies(&[]);
+
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        //casper buys 100 tokens and claims them
+        let info = mock_info(&HumanAddr::from("casper"), &coins(100, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CloseFundraise {}).unwrap();
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::_SendTokens {}).unwrap();
+        let casper = mock_info(&HumanAddr::from("casper"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), casper.clone(), ExecuteMsg::ClaimTokens {}).unwrap();
+
+        //burn
+        let msg = ExecuteMsg::Burn { amount: Uint128(10) };
+        let _ = execute(deps.as_mut(), mock_env(), casper.clone(), msg).unwrap();
+        assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, Uint128(90));
+
+        //send into another contract
+        let msg = ExecuteMsg::Send {
+            contract: HumanAddr::from("dex"),
+            amount: Uint128(20),
+            msg: Some(Binary::from(br#"{"swap":{}}"#.to_vec())),
+        };
+        let res = execute(deps.as_mut(), mock_env(), casper.clone(), msg).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(query_balance(deps.as_ref(), HumanAddr::from("dex")).unwrap().balance, Uint128(20));
+
+        //allowances
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: HumanAddr::from("marcel"),
+            amount: Uint128(50),
+            expires: None,
+        };
+        let _ = execute(deps.as_mut(), mock_env(), casper.clone(), msg).unwrap();
+        let msg = ExecuteMsg::DecreaseAllowance {
+            spender: HumanAddr::from("marcel"),
+            amount: Uint128(10),
+            expires: None,
+        };
+        let _ = execute(deps.as_mut(), mock_env(), casper, msg).unwrap();
+
+        let query_msg = QueryMsg::Allowance {
+            owner: HumanAddr::from("casper"),
+            spender: HumanAddr::from("marcel"),
+        };
+        let allowance: cw20::AllowanceResponse =
+            cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(allowance.allowance, Uint128(40));
+
+        let marcel = mock_info(&HumanAddr::from("marcel"), &[]);
+        let msg = ExecuteMsg::TransferFrom {
+            owner: HumanAddr::from("casper"),
+            recipient: HumanAddr::from("kanye"),
+            amount: Uint128(15),
+        };
+        let _ = execute(deps.as_mut(), mock_env(), marcel.clone(), msg).unwrap();
+        let msg = ExecuteMsg::BurnFrom {
+            owner: HumanAddr::from("casper"),
+            amount: Uint128(5),
+        };
+        let _ = execute(deps.as_mut(), mock_env(), marcel.clone(), msg).unwrap();
+        let msg = ExecuteMsg::SendFrom {
+            owner: HumanAddr::from("casper"),
+            contract: HumanAddr::from("dex"),
+            amount: Uint128(5),
+            msg: None,
+        };
+        let _ = execute(deps.as_mut(), mock_env(), marcel.clone(), msg).unwrap();
+
+        //allowance used up past what is left
+        let msg = ExecuteMsg::TransferFrom {
+            owner: HumanAddr::from("casper"),
+            recipient: HumanAddr::from("kanye"),
+            amount: Uint128(16),
+        };
+        let err = execute(deps.as_mut(), mock_env(), marcel, msg).unwrap_err();
+        assert!(matches!(err, ContractError::Std(_)));
+
+        assert_eq!(query_balance(deps.as_ref(), HumanAddr::from("casper")).unwrap().balance, Uint128(45));
+        assert_eq!(query_balance(deps.as_ref(), HumanAddr::from("kanye")).unwrap().balance, Uint128(15));
+        assert_eq!(query_balance(deps.as_ref(), HumanAddr::from("dex")).unwrap().balance, Uint128(25));
+        assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, Uint128(85));
+
+        let query_msg = QueryMsg::AllAllowances {
+            owner: HumanAddr::from("casper"),
+            start_after: None,
+            limit: None,
+        };
+        let allowances: cw20::AllAllowancesResponse =
+            cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(allowances.allowances.len(), 1);
+        assert_eq!(allowances.allowances[0].allowance, Uint128(15));
+
+        let query_msg = QueryMsg::AllAccounts {
+            start_after: None,
+            limit: None,
+        };
+        let accounts: cw20::AllAccountsResponse =
+            cosmwasm_std::from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(accounts.accounts.len(), 4);
+    }
+
+    #[test]
+    fn invalid_instantiate() {
+        let rate = |min: u128, num: u128, den: u128| RateInit {
+            min: Uint128(min),
+            ratio_num: Uint128(num),
+            ratio_den: Uint128(den),
+        };
+        let auction = |start: u64, floor: u64, duration: u64| DutchAuction {
+            start_price: Decimal::percent(start),
+            floor_price: Decimal::percent(floor),
+            start_time: 0,
+            duration,
+        };
+
+        let cases = vec![
+            (
+                InstantiateMsg { base_conv_ratio_num: Uint128(0), ..mock_instantiate_msg() },
+                ContractError::InvalidBaseRatio {},
+            ),
+            (
+                InstantiateMsg { base_conv_ratio_den: Uint128(0), ..mock_instantiate_msg() },
+                ContractError::InvalidBaseRatio {},
+            ),
+            (
+                InstantiateMsg { rates: Some(vec![rate(10, 0, 2)]), ..mock_instantiate_msg() },
+                ContractError::InvalidRate { min: Uint128(10) },
+            ),
+            (
+                InstantiateMsg { rates: Some(vec![rate(10, 1, 0)]), ..mock_instantiate_msg() },
+                ContractError::InvalidRate { min: Uint128(10) },
+            ),
+            (
+                InstantiateMsg { rates: Some(vec![rate(10, 1, 2), rate(10, 1, 3)]), ..mock_instantiate_msg() },
+                ContractError::DuplicateRateMin { min: Uint128(10) },
+            ),
+            (
+                //base rate already covers 0
+                InstantiateMsg { rates: Some(vec![rate(0, 1, 2)]), ..mock_instantiate_msg() },
+                ContractError::DuplicateRateMin { min: Uint128(0) },
+            ),
+            (
+                InstantiateMsg { fundraise_denom: "".to_string(), ..mock_instantiate_msg() },
+                ContractError::EmptyFundraiseDenom {},
+            ),
+            (
+                InstantiateMsg { fundraise_cw20: Some(HumanAddr::from("token")), ..mock_instantiate_msg() },
+                ContractError::AmbiguousFundraiseAsset {},
+            ),
+            (
+                InstantiateMsg { accepted_denoms: Some(vec!["uusd".to_string()]), ..mock_instantiate_msg() },
+                ContractError::MissingOracle {},
+            ),
+            (
+                InstantiateMsg { dutch_auction: Some(auction(50, 100, 10)), ..mock_instantiate_msg() },
+                ContractError::InvalidDutchAuction {},
+            ),
+            (
+                InstantiateMsg { dutch_auction: Some(auction(100, 0, 10)), ..mock_instantiate_msg() },
+                ContractError::InvalidDutchAuction {},
+            ),
+            (
+                InstantiateMsg { dutch_auction: Some(auction(100, 50, 0)), ..mock_instantiate_msg() },
+                ContractError::InvalidDutchAuction {},
+            ),
+            (
+                InstantiateMsg {
+                    batch_auction: Some(BatchAuction { reserve_price: Decimal::zero() }),
+                    tokens_for_sale: Some(Uint128(100)),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::InvalidBatchAuction {},
+            ),
+            (
+                InstantiateMsg {
+                    batch_auction: Some(BatchAuction { reserve_price: Decimal::one() }),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::MissingTokensForSale {},
+            ),
+            (
+                InstantiateMsg {
+                    batch_auction: Some(BatchAuction { reserve_price: Decimal::one() }),
+                    dutch_auction: Some(auction(100, 50, 10)),
+                    tokens_for_sale: Some(Uint128(100)),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::MultipleAuctions {},
+            ),
+            (
+                InstantiateMsg {
+                    accepted_denoms: Some(vec!["uusd".to_string()]),
+                    oracle: Some(HumanAddr::from("oracle")),
+                    dutch_auction: Some(auction(100, 50, 10)),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::AcceptedDenomsWithAuction {},
+            ),
+            (
+                InstantiateMsg {
+                    accepted_denoms: Some(vec!["uusd".to_string()]),
+                    oracle: Some(HumanAddr::from("oracle")),
+                    batch_auction: Some(BatchAuction { reserve_price: Decimal::one() }),
+                    tokens_for_sale: Some(Uint128(100)),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::AcceptedDenomsWithAuction {},
+            ),
+            (
+                InstantiateMsg {
+                    commit_reveal: Some(CommitReveal {
+                        commit_end: 100,
+                        reveal_end: 100,
+                        penalty: Decimal::percent(10),
+                    }),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::InvalidCommitReveal {},
+            ),
+            (
+                InstantiateMsg {
+                    commit_reveal: Some(CommitReveal {
+                        commit_end: 100,
+                        reveal_end: 200,
+                        penalty: Decimal::percent(10),
+                    }),
+                    end: Some(Expiration::AtHeight(150)),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::CommitRevealOutsideSale {},
+            ),
+            (
+                InstantiateMsg {
+                    commit_reveal: Some(CommitReveal {
+                        commit_end: 100,
+                        reveal_end: 200,
+                        penalty: Decimal::percent(10),
+                    }),
+                    start: Some(Expiration::AtHeight(100)),
+                    end: Some(Expiration::AtHeight(200)),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::CommitRevealOutsideSale {},
+            ),
+            (
+                InstantiateMsg {
+                    commit_reveal: Some(CommitReveal {
+                        commit_end: 100,
+                        reveal_end: 200,
+                        penalty: Decimal::percent(10),
+                    }),
+                    end: Some(Expiration::AtTime(2_000_000_000)),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::CommitRevealOutsideSale {},
+            ),
+            (
+                InstantiateMsg {
+                    bonding_curve: Some(BondingCurve::Linear { base_price: Decimal::zero(), slope: Decimal::zero() }),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::InvalidBondingCurve {},
+            ),
+            (
+                InstantiateMsg {
+                    bonding_curve: Some(BondingCurve::SquareRoot { scale: Decimal::percent(34100) }),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::InvalidBondingCurve {},
+            ),
+            (
+                InstantiateMsg {
+                    bonding_curve: Some(BondingCurve::ConstantProduct {
+                        virtual_reserve: Uint128(1000),
+                        virtual_supply: Uint128(1000),
+                    }),
+                    soft_cap: true,
+                    ..mock_instantiate_msg()
+                },
+                ContractError::BondingCurveConflict {},
+            ),
+            (
+                InstantiateMsg {
+                    bonding_curve: Some(BondingCurve::SquareRoot { scale: Decimal::one() }),
+                    tokens_for_sale: Some(Uint128(100)),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::BondingCurveConflict {},
+            ),
+            (
+                //atomics math tops out at a price of 340
+                InstantiateMsg { dutch_auction: Some(auction(34100, 50, 10)), ..mock_instantiate_msg() },
+                ContractError::InvalidDutchAuction {},
+            ),
+            (
+                InstantiateMsg {
+                    dutch_auction: Some(auction(100, 50, 10)),
+                    rates: Some(vec![rate(10, 1, 2)]),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::RatesWithAuction {},
+            ),
+            (
+                InstantiateMsg {
+                    dutch_auction: Some(auction(100, 50, 10)),
+                    end: Some(Expiration::AtHeight(100_000)),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::AuctionEndsAtHeight {},
+            ),
+            (
+                InstantiateMsg {
+                    accepted_denoms: Some(vec!["uusd".to_string(), "uluna".to_string()]),
+                    oracle: Some(HumanAddr::from("oracle")),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::DuplicateDenom { denom: "uluna".to_string() },
+            ),
+            (
+                InstantiateMsg { fundraise_goal: Uint128(0), ..mock_instantiate_msg() },
+                ContractError::ZeroFundraiseGoal {},
+            ),
+            (
+                InstantiateMsg { max_supply: Some(Uint128(0)), ..mock_instantiate_msg() },
+                ContractError::ZeroMaxSupply {},
+            ),
+            (
+                InstantiateMsg { tokens_for_sale: Some(Uint128(0)), ..mock_instantiate_msg() },
+                ContractError::ZeroTokensForSale {},
+            ),
+            (
+                InstantiateMsg { name: "Sh".to_string(), ..mock_instantiate_msg() },
+                ContractError::InvalidName {},
+            ),
+            (
+                InstantiateMsg { name: "S".repeat(51), ..mock_instantiate_msg() },
+                ContractError::InvalidName {},
+            ),
+            (
+                InstantiateMsg { symbol: "us".to_string(), ..mock_instantiate_msg() },
+                ContractError::InvalidSymbol {},
+            ),
+            (
+                InstantiateMsg { symbol: "ushark2".to_string(), ..mock_instantiate_msg() },
+                ContractError::InvalidSymbol {},
+            ),
+            (
+                InstantiateMsg { symbol: "usharksharkss".to_string(), ..mock_instantiate_msg() },
+                ContractError::InvalidSymbol {},
+            ),
+            (
+                InstantiateMsg { decimals: 19, ..mock_instantiate_msg() },
+                ContractError::InvalidDecimals {},
+            ),
+            (
+                InstantiateMsg { hard_cap: Some(Uint128(99)), ..mock_instantiate_msg() },
+                ContractError::HardCapBelowGoal {},
+            ),
+            (
+                InstantiateMsg {
+                    reserves: Some(vec![ReserveInit {
+                        address: HumanAddr::from("team"),
+                        amount: ReserveAmount::Fixed(Uint128(0)),
+                        vesting: None,
+                    }]),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::EmptyReserve { address: HumanAddr::from("team") },
+            ),
+            (
+                InstantiateMsg {
+                    reserves: Some(vec![ReserveInit {
+                        address: HumanAddr::from("team"),
+                        amount: ReserveAmount::Percent(Decimal::zero()),
+                        vesting: None,
+                    }]),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::EmptyReserve { address: HumanAddr::from("team") },
+            ),
+            (
+                InstantiateMsg {
+                    reserves: Some(vec![
+                        ReserveInit {
+                            address: HumanAddr::from("team"),
+                            amount: ReserveAmount::Fixed(Uint128(10)),
+                            vesting: None,
+                        },
+                        ReserveInit {
+                            address: HumanAddr::from("team"),
+                            amount: ReserveAmount::Fixed(Uint128(20)),
+                            vesting: None,
+                        },
+                    ]),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::DuplicateReserve { address: HumanAddr::from("team") },
+            ),
+            (
+                InstantiateMsg {
+                    reserves: Some(vec![
+                        ReserveInit {
+                            address: HumanAddr::from("team"),
+                            amount: ReserveAmount::Fixed(Uint128(u128::MAX)),
+                            vesting: None,
+                        },
+                        ReserveInit {
+                            address: HumanAddr::from("vault"),
+                            amount: ReserveAmount::Fixed(Uint128(1)),
+                            vesting: None,
+                        },
+                    ]),
+                    ..mock_instantiate_msg()
+                },
+                ContractError::ReserveOverflow {},
+            ),
+        ];
+
+        for (msg, expected) in cases {
+            let mut deps = mock_dependencies(&[]);
+            let info = mock_info(&HumanAddr::from("god"), &[]);
+            let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+            assert_eq!(err, expected);
+        }
+    }
+
+    #[test]
+    fn linear_vesting() {
+        let mut deps = mock_dependencies(&[]);
+
+        //nothing for 100s, then linear until 1000s after close
+        let instantiate_msg = InstantiateMsg {
+            vesting: Some(VestingSchedule { cliff: 100, duration: 1000 }),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        let info = mock_info(&HumanAddr::from("casper"), &coins(100, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        let mut env = mock_env();
+        let closed_at = env.block.time;
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::CloseFundraise {}).unwrap();
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let _ = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::_SendTokens {}).unwrap();
+
+        let casper = mock_info(&HumanAddr::from("casper"), &[]);
+        let err = execute(deps.as_mut(), env.clone(), casper.clone(), ExecuteMsg::ClaimTokens {}).unwrap_err();
+        assert_eq!(err, ContractError::TokensVesting {});
+
+        //still inside the cliff
+        env.block.time = closed_at + 99;
+        let err = execute(deps.as_mut(), env.clone(), casper.clone(), ExecuteMsg::ClaimVested {}).unwrap_err();
+        assert_eq!(err, ContractError::NothingVested {});
+        let status = query_vesting_status(deps.as_ref(), env.clone(), HumanAddr::from("casper")).unwrap();
+        assert_eq!(
+            status,
+            VestingStatusResponse {
+                total: Uint128(100),
+                vested: Uint128(0),
+                claimed: Uint128(0),
+                next_unlock: Some(closed_at + 100),
+            }
+        );
+
+        //a quarter of the way through
+        env.block.time = closed_at + 250;
+        let res = execute(deps.as_mut(), env.clone(), casper.clone(), ExecuteMsg::ClaimVested {}).unwrap();
+        assert_eq!(attr("amount", "25"), res.attributes[2]);
+        assert_eq!(query_balance(deps.as_ref(), HumanAddr::from("casper")).unwrap().balance, Uint128(25));
+        let status = query_vesting_status(deps.as_ref(), env.clone(), HumanAddr::from("casper")).unwrap();
+        assert_eq!(status.next_unlock, Some(closed_at + 1000));
+
+        let err = execute(deps.as_mut(), env.clone(), casper.clone(), ExecuteMsg::ClaimVested {}).unwrap_err();
+        assert_eq!(err, ContractError::NothingVested {});
+
+        //fully vested
+        env.block.time = closed_at + 5000;
+        let status = query_vesting_status(deps.as_ref(), env.clone(), HumanAddr::from("casper")).unwrap();
+        assert_eq!(status.vested, Uint128(100));
+        assert_eq!(status.claimed, Uint128(25));
+        assert_eq!(status.next_unlock, None);
+        let qclaim = query_claimable(deps.as_ref(), env.clone(), HumanAddr::from("casper")).unwrap();
+        assert_eq!(qclaim.claimable, Uint128(75));
+
+        let res = execute(deps.as_mut(), env, casper, ExecuteMsg::ClaimVested {}).unwrap();
+        assert_eq!(attr("amount", "75"), res.attributes[2]);
+        assert_eq!(query_balance(deps.as_ref(), HumanAddr::from("casper")).unwrap().balance, Uint128(100));
+
+        //schedules have to take some time and can't have a cliff past their end
+        for vesting in &[VestingSchedule { cliff: 0, duration: 0 }, VestingSchedule { cliff: 1001, duration: 1000 }] {
+            let instantiate_msg = InstantiateMsg { vesting: Some(*vesting), ..mock_instantiate_msg() };
+            let err = instantiate_msg.validate().unwrap_err();
+            assert_eq!(err, ContractError::InvalidVestingSchedule {});
+        }
+    }
+
+    #[test]
+    fn reserves_minted_at_close() {
+        let mut deps = mock_dependencies(&[]);
+
+        //a fifth of the supply to the team, 50 tokens to the treasury vesting over 1000s
+        let instantiate_msg = InstantiateMsg {
+            reserves: Some(vec![
+                ReserveInit {
+                    address: HumanAddr::from("team"),
+                    amount: ReserveAmount::Percent(Decimal::percent(20)),
+                    vesting: None,
+                },
+                ReserveInit {
+                    address: HumanAddr::from("vault"),
+                    amount: ReserveAmount::Fixed(Uint128(50)),
+                    vesting: Some(VestingSchedule { cliff: 0, duration: 1000 }),
+                },
+            ]),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        let info = mock_info(&HumanAddr::from("casper"), &coins(150, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        let mut env = mock_env();
+        let closed_at = env.block.time;
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::CloseFundraise {}).unwrap();
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::_SendTokens {}).unwrap();
+        assert_eq!(attr("minted", "150"), res.attributes[1]);
+        assert_eq!(attr("reserved", "100"), res.attributes[2]);
+
+        //150 + 50 is the 80% left after the team's share
+        let qinfo = query_token_info(deps.as_ref()).unwrap();
+        assert_eq!(qinfo.total_supply, Uint128(250));
+        assert_eq!(query_balance(deps.as_ref(), HumanAddr::from("team")).unwrap().balance, Uint128(50));
+        assert_eq!(query_balance(deps.as_ref(), HumanAddr::from("vault")).unwrap().balance, Uint128(0));
+
+        let qres = query_reserves(deps.as_ref()).unwrap();
+        assert_eq!(qres.total_reserved, Uint128(100));
+        let reserve = |name: &str| qres.reserves.iter().find(|r| r.address == HumanAddr::from(name)).unwrap();
+        assert_eq!(reserve("team").claimed, Uint128(50));
+        assert_eq!(reserve("vault").allocation, Uint128(50));
+
+        let team = mock_info(&HumanAddr::from("team"), &[]);
+        let err = execute(deps.as_mut(), env.clone(), team, ExecuteMsg::ClaimReserve {}).unwrap_err();
+        assert_eq!(err, ContractError::NoVesting {});
+
+        env.block.time = closed_at + 500;
+        let vault = mock_info(&HumanAddr::from("vault"), &[]);
+        let res = execute(deps.as_mut(), env, vault, ExecuteMsg::ClaimReserve {}).unwrap();
+        assert_eq!(attr("amount", "25"), res.attributes[2]);
+        assert_eq!(query_balance(deps.as_ref(), HumanAddr::from("vault")).unwrap().balance, Uint128(25));
+
+        //reserves can't swallow the whole supply
+        let instantiate_msg = InstantiateMsg {
+            reserves: Some(vec![ReserveInit {
+                address: HumanAddr::from("team"),
+                amount: ReserveAmount::Percent(Decimal::one()),
+                vesting: None,
+            }]),
+            ..mock_instantiate_msg()
+        };
+        let err = instantiate_msg.validate().unwrap_err();
+        assert_eq!(err, ContractError::ReservesExceedSupply {});
+    }
+
+    #[test]
+    fn max_supply_scales_allocations() {
+        let mut deps = mock_dependencies(&[]);
+
+        let instantiate_msg = InstantiateMsg {
+            max_supply: Some(Uint128(100)),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        let info = mock_info(&HumanAddr::from("casper"), &coins(100, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+        let info = mock_info(&HumanAddr::from("marcel"), &coins(50, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        //150 tokens allocated but only 100 can exist
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CloseFundraise {}).unwrap();
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::_SendTokens {}).unwrap();
+        assert_eq!(attr("minted", "100"), res.attributes[1]);
+
+        let qclaim = query_claimable(deps.as_ref(), mock_env(), HumanAddr::from("casper")).unwrap();
+        assert_eq!(qclaim.allocation, Uint128(66));
+        let casper = mock_info(&HumanAddr::from("casper"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), casper.clone(), ExecuteMsg::ClaimTokens {}).unwrap();
+        let marcel = mock_info(&HumanAddr::from("marcel"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), marcel.clone(), ExecuteMsg::ClaimTokens {}).unwrap();
+        assert_eq!(query_balance(deps.as_ref(), HumanAddr::from("casper")).unwrap().balance, Uint128(66));
+        assert_eq!(query_balance(deps.as_ref(), HumanAddr::from("marcel")).unwrap().balance, Uint128(33));
+
+        //the third of each contribution that bought nothing comes back
+        let res = execute(deps.as_mut(), mock_env(), casper.clone(), ExecuteMsg::ClaimRefund {}).unwrap();
+        assert_eq!(attr("amount", "33"), res.attributes[2]);
+        let err = execute(deps.as_mut(), mock_env(), casper, ExecuteMsg::ClaimRefund {}).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyRefunded {});
+        let res = execute(deps.as_mut(), mock_env(), marcel, ExecuteMsg::ClaimRefund {}).unwrap();
+        assert_eq!(attr("amount", "16"), res.attributes[2]);
+
+        let qfund = query_fundraise(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(qfund.max_supply, Some(Uint128(100)));
+        assert_eq!(qfund.proceeds_remaining, Uint128(100));
+    }
+
+    #[test]
+    fn oversubscribed_fixed_supply() {
+        let mut deps = mock_dependencies(&[]);
+
+        //100 tokens on offer at 1 uluna each
+        let instantiate_msg = InstantiateMsg {
+            tokens_for_sale: Some(Uint128(100)),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        //three times the implied raise comes in
+        for name in &["casper", "marcel", "rick"] {
+            let info = mock_info(&HumanAddr::from(*name), &coins(100, "uluna"));
+            let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+        }
+
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CloseFundraise {}).unwrap();
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::_SendTokens {}).unwrap();
+        assert_eq!(attr("minted", "100"), res.attributes[1]);
+
+        //everyone gets a third of the supply and two thirds of their funds back, rounded down
+        let mut claimed = Uint128(0);
+        for name in &["casper", "marcel", "rick"] {
+            let info = mock_info(&HumanAddr::from(*name), &[]);
+            let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::ClaimTokens {}).unwrap();
+            assert_eq!(attr("amount", "33"), res.attributes[2]);
+            claimed += Uint128(33);
+            let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimRefund {}).unwrap();
+            assert_eq!(attr("amount", "66"), res.attributes[2]);
+        }
+
+        //the dust stays with the contract, never more than the supply is handed out
+        let qinfo = query_token_info(deps.as_ref()).unwrap();
+        assert_eq!(qinfo.total_supply, Uint128(100));
+        assert!(claimed <= qinfo.total_supply);
+        let qfund = query_fundraise(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(qfund.proceeds_remaining, Uint128(100));
+
+        //tiers would break pro-rata shares
+        let instantiate_msg = InstantiateMsg {
+            tokens_for_sale: Some(Uint128(100)),
+            rates: Some(vec![RateInit {
+                min: Uint128(50),
+                ratio_num: Uint128(1),
+                ratio_den: Uint128(2),
+            }]),
+            ..mock_instantiate_msg()
+        };
+        let err = instantiate_msg.validate().unwrap_err();
+        assert_eq!(err, ContractError::RatesWithFixedSupply {});
+    }
+
+    #[test]
+    fn contribution_limits() {
+        let mut deps = mock_dependencies(&[]);
+
+        let instantiate_msg = InstantiateMsg {
+            min_contribution: Some(Uint128(10)),
+            max_contribution: Some(Uint128(50)),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        let qfund = query_fundraise(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(qfund.min_contribution, Some(Uint128(10)));
+        assert_eq!(qfund.max_contribution, Some(Uint128(50)));
+
+        //first ticket has to reach the min
+        let info = mock_info(&HumanAddr::from("casper"), &coins(5, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap_err();
+        assert_eq!(err, ContractError::BelowMinContribution { min: Uint128(10) });
+
+        let info = mock_info(&HumanAddr::from("casper"), &coins(10, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        //top ups only need to keep the running balance above it
+        let info = mock_info(&HumanAddr::from("casper"), &coins(5, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        let info = mock_info(&HumanAddr::from("casper"), &coins(36, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap_err();
+        assert_eq!(err, ContractError::AboveMaxContribution { max: Uint128(50) });
+
+        let qsim = query_simulate_contribution(deps.as_ref(), mock_env(), HumanAddr::from("casper"), Uint128(35), None, None).unwrap();
+        assert!(qsim.accepted);
+        let qcont = query_contribution(deps.as_ref(), HumanAddr::from("casper")).unwrap();
+        assert_eq!(qcont.balance, Uint128(15));
+
+        let instantiate_msg = InstantiateMsg {
+            min_contribution: Some(Uint128(60)),
+            max_contribution: Some(Uint128(50)),
+            ..mock_instantiate_msg()
+        };
+        let err = instantiate_msg.validate().unwrap_err();
+        assert_eq!(err, ContractError::InvalidContributionLimits {});
+
+        //what's left under the hard cap has to reach the min too
+        let mut deps = mock_dependencies(&[]);
+        let instantiate_msg = InstantiateMsg {
+            min_contribution: Some(Uint128(10)),
+            hard_cap: Some(Uint128(105)),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        let info = mock_info(&HumanAddr::from("casper"), &coins(100, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+        let info = mock_info(&HumanAddr::from("marcel"), &coins(20, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap_err();
+        assert_eq!(err, ContractError::BelowMinContribution { min: Uint128(10) });
+        assert_eq!(query_contribution(deps.as_ref(), HumanAddr::from("marcel")).unwrap().balance, Uint128(0));
+    }
+
+    #[test]
+    fn allowlisted_sale() {
+        let mut deps = mock_dependencies(&[]);
+
+        let instantiate_msg = InstantiateMsg {
+            allowlisted: true,
+            max_contribution: Some(Uint128(50)),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        let info = mock_info(&HumanAddr::from("casper"), &coins(10, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap_err();
+        assert_eq!(err, ContractError::NotAllowlisted {});
+
+        let msg = ExecuteMsg::UpdateAllowlist {
+            add: vec![
+                AllowlistEntry { address: HumanAddr::from("casper"), cap: Some(Uint128(20)) },
+                AllowlistEntry { address: HumanAddr::from("marcel"), cap: None },
+                AllowlistEntry { address: HumanAddr::from("rick"), cap: None },
+            ],
+            remove: vec![],
+        };
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        //casper's own cap is tighter than the sale wide one
+        let info = mock_info(&HumanAddr::from("casper"), &coins(30, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap_err();
+        assert_eq!(err, ContractError::AboveMaxContribution { max: Uint128(20) });
+        let info = mock_info(&HumanAddr::from("casper"), &coins(20, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        //marcel falls back to the sale wide max
+        let info = mock_info(&HumanAddr::from("marcel"), &coins(51, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap_err();
+        assert_eq!(err, ContractError::AboveMaxContribution { max: Uint128(50) });
+
+        let msg = ExecuteMsg::UpdateAllowlist {
+            add: vec![],
+            remove: vec![HumanAddr::from("rick")],
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let qsim = query_simulate_contribution(deps.as_ref(), mock_env(), HumanAddr::from("rick"), Uint128(10), None, None).unwrap();
+        assert_eq!(qsim.error, Some(ContractError::NotAllowlisted {}.to_string()));
+
+        //two pages of one cover everyone still listed
+        let first = query_allowlist(deps.as_ref(), None, Some(1)).unwrap();
+        assert!(first.allowlisted);
+        assert_eq!(first.entries.len(), 1);
+        let second = query_allowlist(deps.as_ref(), Some(first.entries[0].address.clone()), None).unwrap();
+        assert_eq!(second.entries.len(), 1);
+        let mut listed = vec![first.entries[0].address.clone(), second.entries[0].address.clone()];
+        listed.sort();
+        assert_eq!(listed, vec![HumanAddr::from("casper"), HumanAddr::from("marcel")]);
+    }
+
+    #[test]
+    fn merkle_allowlisted_sale() {
+        let mut deps = mock_dependencies(&[]);
+
+        let caps = vec![
+            (HumanAddr::from("casper"), Uint128(30)),
+            (HumanAddr::from("marcel"), Uint128(20)),
+            (HumanAddr::from("rick"), Uint128(10)),
+        ];
+        let tree = MerkleTree::new(caps.iter().map(|(a, c)| leaf_hash(a, *c)).collect());
+        let proof = |tree: &MerkleTree, index: usize| -> Vec<Binary> {
+            tree.proof(index).unwrap().iter().map(|h| Binary::from(h.to_vec())).collect()
+        };
+
+        //the root has to be a whole sha256 hash
+        let instantiate_msg = InstantiateMsg {
+            merkle_root: Some(Binary::from(tree.root().unwrap()[..31].to_vec())),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let err = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidMerkleRoot {});
+
+        let instantiate_msg = InstantiateMsg {
+            merkle_root: Some(Binary::from(tree.root().unwrap().to_vec())),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        let info = mock_info(&HumanAddr::from("casper"), &coins(10, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap_err();
+        assert_eq!(err, ContractError::MissingAllocationProof {});
+
+        //casper can't claim a bigger cap than the tree gives them
+        let msg = ExecuteMsg::AddFunds {
+            allocation: Some(AllocationProof { cap: Uint128(100), proof: proof(&tree, 0) }),
+            attestation: None,
+        };
+        let info = mock_info(&HumanAddr::from("casper"), &coins(10, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidAllocationProof {});
+
+        let msg = ExecuteMsg::AddFunds {
+            allocation: Some(AllocationProof { cap: Uint128(30), proof: proof(&tree, 0) }),
+            attestation: None,
+        };
+        let info = mock_info(&HumanAddr::from("casper"), &coins(20, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+
+        //usage is tracked across contributions
+        let info = mock_info(&HumanAddr::from("casper"), &coins(11, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::AboveMaxContribution { max: Uint128(30) });
+
+        let qsim = query_simulate_contribution(
+            deps.as_ref(),
+            mock_env(),
+            HumanAddr::from("marcel"),
+            Uint128(20),
+            Some(AllocationProof { cap: Uint128(20), proof: proof(&tree, 1) }),
+            None,
+        )
+        .unwrap();
+        assert!(qsim.accepted);
+
+        //rotating the root raises casper's cap
+        let caps = vec![(HumanAddr::from("casper"), Uint128(50))];
+        let rotated = MerkleTree::new(caps.iter().map(|(a, c)| leaf_hash(a, *c)).collect());
+        let msg = ExecuteMsg::UpdateMerkleRoot {
+            merkle_root: Some(Binary::from(rotated.root().unwrap().to_vec())),
+        };
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::AddFunds {
+            allocation: Some(AllocationProof { cap: Uint128(50), proof: proof(&rotated, 0) }),
+            attestation: None,
+        };
+        let info = mock_info(&HumanAddr::from("casper"), &coins(11, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let qcont = query_contribution(deps.as_ref(), HumanAddr::from("casper")).unwrap();
+        assert_eq!(qcont.balance, Uint128(31));
+    }
+
+    #[test]
+    fn kyc_attestations() {
+        let mut deps = mock_dependencies(&[]);
+
+        let instantiate_msg = InstantiateMsg {
+            kyc_required: true,
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        let key = SigningKey::from_bytes(&[7u8; 32]).unwrap();
+        let pubkey = Binary::from(VerifyingKey::from(&key).to_encoded_point(true).as_bytes().to_vec());
+        let attest = |address: &str, expires: Expiration, max_amount: u128| -> Attestation {
+            let mut attestation = Attestation {
+                address: HumanAddr::from(address),
+                expires,
+                max_amount: Uint128(max_amount),
+                signature: Binary::default(),
+            };
+            let payload = to_vec(&attestation.payload(HumanAddr::from(MOCK_CONTRACT_ADDR))).unwrap();
+            let signature: Signature = key.sign_digest(Sha256::new().chain(&payload));
+            attestation.signature = Binary::from(signature.as_ref().to_vec());
+            attestation
+        };
+        let add_funds = |attestation: Option<Attestation>| ExecuteMsg::AddFunds {
+            allocation: None,
+            attestation,
+        };
+
+        let info = mock_info(&HumanAddr::from("casper"), &coins(30, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, add_funds(None)).unwrap_err();
+        assert_eq!(err, ContractError::AttestationRequired {});
+
+        //nobody is trusted to sign yet
+        let valid = attest("casper", Expiration::Never {}, 50);
+        let info = mock_info(&HumanAddr::from("casper"), &coins(30, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, add_funds(Some(valid.clone()))).unwrap_err();
+        assert_eq!(err, ContractError::InvalidAttestation {});
+
+        //attesters have to be secp256k1 keys, compressed or not
+        let msg = ExecuteMsg::UpdateAttesters { add: vec![Binary::from(vec![2u8; 32])], remove: vec![] };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidAttester {});
+
+        let msg = ExecuteMsg::UpdateAttesters { add: vec![pubkey.clone()], remove: vec![] };
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let qfund = query_fundraise(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(qfund.attesters, vec![pubkey]);
+
+        let expired = attest("casper", Expiration::AtHeight(mock_env().block.height), 50);
+        let info = mock_info(&HumanAddr::from("casper"), &coins(30, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, add_funds(Some(expired))).unwrap_err();
+        assert_eq!(err, ContractError::AttestationExpired {});
+
+        let info = mock_info(&HumanAddr::from("casper"), &coins(60, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, add_funds(Some(valid.clone()))).unwrap_err();
+        assert_eq!(err, ContractError::AboveAttestedAmount { max: Uint128(50) });
+
+        let info = mock_info(&HumanAddr::from("casper"), &coins(30, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, add_funds(Some(valid.clone()))).unwrap();
+
+        //the limit covers everything the address puts in
+        let info = mock_info(&HumanAddr::from("casper"), &coins(30, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, add_funds(Some(valid.clone()))).unwrap_err();
+        assert_eq!(err, ContractError::AboveAttestedAmount { max: Uint128(50) });
+
+        //someone else's attestation, or one with a raised limit, doesn't verify
+        let info = mock_info(&HumanAddr::from("marcel"), &coins(30, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, add_funds(Some(valid.clone()))).unwrap_err();
+        assert_eq!(err, ContractError::InvalidAttestation {});
+        let tampered = Attestation { max_amount: Uint128(1000), ..valid };
+        let info = mock_info(&HumanAddr::from("casper"), &coins(30, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, add_funds(Some(tampered))).unwrap_err();
+        assert_eq!(err, ContractError::InvalidAttestation {});
+    }
+
+    #[test]
+    fn cw20_fundraise() {
+        let mut deps = mock_dependencies(&[]);
+
+        let instantiate_msg = InstantiateMsg {
+            fundraise_denom: "".to_string(),
+            fundraise_cw20: Some(HumanAddr::from("usdtoken")),
+            hard_cap: Some(Uint128(150)),
+            treasury: Some(HumanAddr::from("vault")),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+
+        let qfund = query_fundraise(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(qfund.fundraise_asset, FundraiseAsset::Cw20 { contract_addr: HumanAddr::from("usdtoken") });
+
+        //native coins aren't accepted
+        let info = mock_info(&HumanAddr::from("casper"), &coins(100, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap_err();
+        assert_eq!(err, ContractError::InvalidFundraiseAsset {});
+
+        //only the sale's token can call Receive
+        let receive = |sender: &str, amount: u128| ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(sender),
+            amount: Uint128(amount),
+            msg: None,
+        });
+        let info = mock_info(&HumanAddr::from("faketoken"), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, receive("casper", 100)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidFundraiseAsset {});
+
+        let info = mock_info(&HumanAddr::from("usdtoken"), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, receive("casper", 100)).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(attr("id", "casper"), res.attributes[1]);
+
+        //an explicit AddFunds works too, and the excess goes back as tokens
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from("marcel"),
+            amount: Uint128(80),
+            msg: Some(to_binary(&ReceiveMsg::AddFunds { allocation: None, attestation: None }).unwrap()),
+        });
+        let info = mock_info(&HumanAddr::from("usdtoken"), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(attr("amount", "50"), res.attributes[2]);
+        assert_eq!(attr("closed", "true"), res.attributes[4]);
+        assert_eq!(2, res.messages.len());
+        assert_eq!(
+            res.messages[0],
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("usdtoken"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient: HumanAddr::from("marcel"), amount: Uint128(30) }).unwrap(),
+                send: vec![],
+            })
+        );
+
+        let qfund = query_fundraise(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(qfund.fundraise_bal, Uint128(150));
+
+        //proceeds are withdrawn as tokens
+        let owner = mock_info(&HumanAddr::from("god"), &[]);
+        let res = execute(deps.as_mut(), mock_env(), owner, ExecuteMsg::WithdrawProceeds { amount: None }).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("usdtoken"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient: HumanAddr::from("vault"), amount: Uint128(150) }).unwrap(),
+                send: vec![],
+            })]
+        );
+    }
+
+    /// prices the mock oracle answers with, set when it is instantiated
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    struct MockOracleMsg {
+        prices: Vec<(String, Decimal)>,
+    }
+
+    const PRICES: Map<&[u8], Decimal> = Map::new("prices");
+
+    fn oracle_instantiate(deps: DepsMut, _env: Env, _info: MessageInfo, msg: MockOracleMsg) -> StdResult<Response> {
+        for (denom, price) in msg.prices {
+            PRICES.save(deps.storage, denom.as_bytes(), &price)?;
+        }
+        Ok(Response::default())
+    }
+
+    fn oracle_execute(deps: DepsMut, env: Env, info: MessageInfo, msg: MockOracleMsg) -> StdResult<Response> {
+        oracle_instantiate(deps, env, info, msg)
+    }
+
+    fn oracle_query(deps: Deps, _env: Env, msg: OracleQueryMsg) -> StdResult<Binary> {
+        match msg {
+            OracleQueryMsg::Price { denom, quote } => {
+                assert_eq!(quote, "uluna");
+                to_binary(&PriceResponse { rate: PRICES.load(deps.storage, denom.as_bytes())? })
+            }
+        }
+    }
+
+    fn mock_oracle() -> Box<dyn Contract<Empty>> {
+        Box::new(ContractWrapper::new(oracle_execute, oracle_instantiate, oracle_query))
+    }
+
+    fn ico_contract() -> Box<dyn Contract<Empty>> {
+        Box::new(ContractWrapper::new(execute, instantiate, query))
+    }
+
+    #[test]
+    fn multi_asset_contributions() {
+        let mut app: App = App::new(
+            Box::new(MockApi::default()),
+            mock_env().block,
+            SimpleBank {},
+            || Box::new(MockStorage::new()),
+        );
+
+        let oracle_id = app.store_code(mock_oracle());
+        let ico_id = app.store_code(ico_contract());
+        let prices = MockOracleMsg {
+            prices: vec![
+                ("uusd".to_string(), Decimal::percent(10)),
+                ("ukrw".to_string(), Decimal::permille(1)),
+            ],
+        };
+        let oracle = app.instantiate_contract(oracle_id, "god", &prices, &[], "oracle").unwrap();
+        let instantiate_msg = InstantiateMsg {
+            accepted_denoms: Some(vec!["uusd".to_string(), "ukrw".to_string()]),
+            oracle: Some(oracle),
+            hard_cap: Some(Uint128(150)),
+            ..mock_instantiate_msg()
+        };
+        let ico = app.instantiate_contract(ico_id, "god", &instantiate_msg, &[], "ico").unwrap();
+
+        let casper = HumanAddr::from("casper");
+        let marcel = HumanAddr::from("marcel");
+        app.set_bank_balance(casper.clone(), vec![coin(100, "ubtc"), coin(50, "uluna"), coin(300, "uusd")]).unwrap();
+        app.set_bank_balance(marcel.clone(), coins(100_000, "ukrw")).unwrap();
+        let add_funds = ExecuteMsg::AddFunds { allocation: None, attestation: None };
+
+        //coins the sale doesn't take are turned away, even alongside ones it does
+        let err = app.execute_contract(casper.clone(), ico.clone(), &add_funds, &coins(100, "ubtc")).unwrap_err();
+        assert_eq!(err, ContractError::InvalidFundraiseAsset {}.to_string());
+        let mixed = [coin(100, "ubtc"), coin(50, "uluna"), coin(300, "uusd")];
+        let err = app.execute_contract(casper.clone(), ico.clone(), &add_funds, &mixed).unwrap_err();
+        assert_eq!(err, ContractError::InvalidFundraiseAsset {}.to_string());
+        assert_eq!(app.wrap().query_balance(casper.clone(), "ubtc").unwrap(), coin(100, "ubtc"));
+
+        //300 uusd is worth 30 uluna
+        app.execute_contract(casper.clone(), ico.clone(), &add_funds, &[coin(50, "uluna"), coin(300, "uusd")]).unwrap();
+        let qcontrib: ContributionResponse = app
+            .wrap()
+            .query_wasm_smart(ico.clone(), &QueryMsg::Contribution { address: casper.clone() })
+            .unwrap();
+        assert_eq!(qcontrib.balance, Uint128(80));
+        assert_eq!(qcontrib.deposits, vec![coin(50, "uluna"), coin(300, "uusd")]);
+
+        //100_000 ukrw is worth 100 but only 70 fits under the cap, the rest goes back in ukrw
+        app.execute_contract(marcel.clone(), ico.clone(), &add_funds, &coins(100_000, "ukrw")).unwrap();
+        assert_eq!(app.wrap().query_balance(marcel.clone(), "ukrw").unwrap(), coin(30_000, "ukrw"));
+
+        let qfund: FundraiseInfoResponse = app.wrap().query_wasm_smart(ico.clone(), &QueryMsg::FundraiseInfo {}).unwrap();
+        assert_eq!(qfund.fundraise_bal, Uint128(150));
+        assert_eq!(qfund.status, SaleStatus::Closed);
+        assert_eq!(qfund.deposits, vec![coin(70_000, "ukrw"), coin(50, "uluna"), coin(300, "uusd")]);
+
+        //proceeds come out of every denom in step
+        let withdraw = ExecuteMsg::WithdrawProceeds { amount: Some(Uint128(75)) };
+        app.execute_contract(HumanAddr::from("god"), ico.clone(), &withdraw, &[]).unwrap();
+        assert_eq!(
+            app.wrap().query_all_balances(HumanAddr::from("god")).unwrap(),
+            vec![coin(35_000, "ukrw"), coin(25, "uluna"), coin(150, "uusd")]
+        );
+        let withdraw = ExecuteMsg::WithdrawProceeds { amount: None };
+        app.execute_contract(HumanAddr::from("god"), ico.clone(), &withdraw, &[]).unwrap();
+        assert_eq!(app.wrap().query_all_balances(ico).unwrap(), vec![]);
     }
 
     #[test]
-    fn token_created() {
+    fn dutch_auction() {
         let mut deps = mock_dependencies(&[]);
+        let mut env = mock_env();
+        let start = env.block.time;
 
-        // instantiate a contract
+        //falls from 2 uluna a token to 0.5 over 1000 seconds
         let instantiate_msg = InstantiateMsg {
-            fundraise_goal: Uint128(100),
-            base_conv_ratio_num: Uint128(1),
-            base_conv_ratio_den: Uint128(1),
-            fundraise_denom: "uluna".to_string(),
-            name: "Shark Coin".to_string(),
-            symbol: "ushark".to_string(),
-            decimals: 0,
-            rates: None,
+            dutch_auction: Some(DutchAuction {
+                start_price: Decimal::percent(200),
+                floor_price: Decimal::percent(50),
+                start_time: start,
+                duration: 1000,
+            }),
+            ..mock_instantiate_msg()
         };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), env.clone(), info, instantiate_msg).unwrap();
+
+        let qprice = query_current_price(deps.as_ref(), env.clone()).unwrap();
+        assert_eq!(qprice.price, Decimal::percent(200));
+        assert!(!qprice.settled);
 
+        //100 uluna buys 50 tokens at the start price
+        let info = mock_info(&HumanAddr::from("casper"), &coins(100, "uluna"));
+        let _ = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        //halfway down the same 100 buys 80
+        env.block.time = start + 500;
+        let qprice = query_current_price(deps.as_ref(), env.clone()).unwrap();
+        assert_eq!(qprice.price, Decimal::percent(125));
+        let qsim = query_simulate_contribution(deps.as_ref(), env.clone(), HumanAddr::from("marcel"), Uint128(100), None, None).unwrap();
+        assert_eq!(qsim.tokens, Uint128(80));
+        let info = mock_info(&HumanAddr::from("marcel"), &coins(100, "uluna"));
+        let _ = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        //no refunds while the price can still fall
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let err = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::ClaimRefund {}).unwrap_err();
+        assert_eq!(err, ContractError::RefundsUnavailable {});
+
+        //closing at 1.1 uluna a token settles everyone there
+        env.block.time = start + 600;
         let info = mock_info(&HumanAddr::from("god"), &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        let _ = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::CloseFundraise {}).unwrap();
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::_SendTokens {}).unwrap();
+        assert_eq!(attr("minted", "130"), res.attributes[1]);
 
-        //check if token exists
-        let qtoken = query_token_info(deps.as_ref()).unwrap();
+        env.block.time = start + 900;
+        let qprice = query_current_price(deps.as_ref(), env.clone()).unwrap();
+        assert_eq!(qprice.price, Decimal::percent(110));
+        assert!(qprice.settled);
+        let qfund = query_fundraise(deps.as_ref(), env.clone()).unwrap();
+        assert_eq!(qfund.clearing_price, Some(Decimal::percent(110)));
+        assert_eq!(qfund.proceeds_remaining, Uint128(143));
+
+        //tokens stay as bought, the overpayment comes back
+        for (name, tokens, refund) in &[("casper", "50", "45"), ("marcel", "80", "12")] {
+            let info = mock_info(&HumanAddr::from(*name), &[]);
+            let res = execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::ClaimTokens {}).unwrap();
+            assert_eq!(attr("amount", *tokens), res.attributes[2]);
+            let res = execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::ClaimRefund {}).unwrap();
+            assert_eq!(attr("amount", *refund), res.attributes[2]);
+            let err = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::ClaimRefund {}).unwrap_err();
+            assert_eq!(err, ContractError::AlreadyRefunded {});
+        }
+
+        //closing after the end settles at the price when contributions stopped, not whatever it fell to since
+        let mut deps = mock_dependencies(&[]);
+        let instantiate_msg = InstantiateMsg {
+            dutch_auction: Some(DutchAuction {
+                start_price: Decimal::percent(200),
+                floor_price: Decimal::percent(50),
+                start_time: start,
+                duration: 1000,
+            }),
+            end: Some(Expiration::AtTime(start + 500)),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        let info = mock_info(&HumanAddr::from("casper"), &coins(100, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+        let mut env = mock_env();
+        env.block.time = start + 900;
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let _ = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::CloseFundraise {}).unwrap();
+        let qfund = query_fundraise(deps.as_ref(), env.clone()).unwrap();
+        assert_eq!(qfund.clearing_price, Some(Decimal::percent(125)));
+        assert_eq!(qfund.proceeds_remaining, Uint128(63));
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let res = execute(deps.as_mut(), env, info, ExecuteMsg::ClaimRefund {}).unwrap();
+        assert_eq!(attr("amount", "37"), res.attributes[2]);
+
+        //an auction closes itself once everything on offer is bought
+        let mut deps = mock_dependencies(&[]);
+        let instantiate_msg = InstantiateMsg {
+            dutch_auction: Some(DutchAuction {
+                start_price: Decimal::percent(200),
+                floor_price: Decimal::percent(50),
+                start_time: start,
+                duration: 1000,
+            }),
+            tokens_for_sale: Some(Uint128(50)),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        let info = mock_info(&HumanAddr::from("casper"), &coins(60, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        //the last buyer only pays for the 20 tokens left and gets the rest back
+        let info = mock_info(&HumanAddr::from("marcel"), &coins(100, "uluna"));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+        assert_eq!(attr("amount", "40"), res.attributes[2]);
+        assert_eq!(attr("returned", "60"), res.attributes[3]);
+        assert_eq!(attr("closed", "true"), res.attributes[4]);
         assert_eq!(
-            qtoken,
-            TokenInfoResponse {
-                name: "Shark Coin".to_string(),
-                symbol: "ushark".to_string(),
-                decimals: 0,
-                total_supply: Uint128(0),
-            }
+            res.messages[0],
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: HumanAddr::from("marcel"),
+                amount: coins(60, "uluna"),
+            })
         );
+
+        //nobody gets scaled down at close
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::_SendTokens {}).unwrap();
+        assert_eq!(attr("minted", "50"), res.attributes[1]);
+        let qclaim = query_claimable(deps.as_ref(), mock_env(), HumanAddr::from("marcel")).unwrap();
+        assert_eq!(qclaim.allocation, Uint128(20));
+
+        //sales sized in 18 decimal units settle without overflowing
+        let whole = 1_000_000_000_000_000_000u128;
+        let mut deps = mock_dependencies(&[]);
+        let instantiate_msg = InstantiateMsg {
+            dutch_auction: Some(DutchAuction {
+                start_price: Decimal::percent(200),
+                floor_price: Decimal::percent(50),
+                start_time: start,
+                duration: 1000,
+            }),
+            fundraise_goal: Uint128(1_000 * whole),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        let info = mock_info(&HumanAddr::from("casper"), &coins(1_000 * whole, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = start + 600;
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::CloseFundraise {}).unwrap();
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let _ = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::_SendTokens {}).unwrap();
+        let qfund = query_fundraise(deps.as_ref(), env.clone()).unwrap();
+        assert_eq!(qfund.proceeds_remaining, Uint128(550 * whole));
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let res = execute(deps.as_mut(), env, info, ExecuteMsg::ClaimRefund {}).unwrap();
+        assert_eq!(attr("amount", (450 * whole).to_string()), res.attributes[2]);
+
+        //fixed price sales have no auction price
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+        assert!(query_current_price(deps.as_ref(), mock_env()).is_err());
     }
 
     #[test]
-    fn close_and_send() {
+    fn batch_auction() {
         let mut deps = mock_dependencies(&[]);
 
-        // instantiate a contract
+        //100 tokens go to the highest bids, none below 0.5 uluna a token
         let instantiate_msg = InstantiateMsg {
-            fundraise_goal: Uint128(100),
-            base_conv_ratio_num: Uint128(1),
-            base_conv_ratio_den: Uint128(1),
-            fundraise_denom: "uluna".to_string(),
-            name: "Shark Coin".to_string(),
-            symbol: "ushark".to_string(),
-            decimals: 0,
-            rates: None,
+            batch_auction: Some(BatchAuction { reserve_price: Decimal::percent(50) }),
+            tokens_for_sale: Some(Uint128(100)),
+            ..mock_instantiate_msg()
         };
-
         let info = mock_info(&HumanAddr::from("god"), &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
 
-        //add funds
-        let sender = HumanAddr::from("casper");
-        let balance = coins(100, "uluna");
-        let info = mock_info(&sender, &balance);
-        let msg = ExecuteMsg::AddFunds {};
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(attr("action", "add_funds"), res.attributes[0]);
+        let bid = |percent: u64| ExecuteMsg::PlaceBid {
+            max_price: Decimal::percent(percent),
+            allocation: None,
+            attestation: None,
+        };
 
-        //add funds
-        let sender = HumanAddr::from("marcel");
-        let balance = coins(50, "uluna");
-        let info = mock_info(&sender, &balance);
-        let msg = ExecuteMsg::AddFunds {};
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(attr("action", "add_funds"), res.attributes[0]);
+        //contributions have to be bids at or above the reserve
+        let info = mock_info(&HumanAddr::from("casper"), &coins(100, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap_err();
+        assert_eq!(err, ContractError::BidRequired {});
+        let err = execute(deps.as_mut(), mock_env(), info, bid(40)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidBidPrice {});
 
-        //close fundraise
-        let sender = HumanAddr::from("casper");
-        let info = mock_info(&sender, &[]);
-        let msg = ExecuteMsg::CloseFundraise {};
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(1, res.messages.len());
-        assert_eq!(attr("action", "close_fundraise"), res.attributes[0]);
-        
-        let sendmsg = &res.messages[0];
-        match sendmsg {
-            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg:_, send }) => {
-                assert_eq!(send, &[]);
-                assert_eq!(contract_addr, &HumanAddr::from(MOCK_CONTRACT_ADDR));
-            }
-            _ => panic!("Unexpected message: {:?}", sendmsg),
+        for (name, amount, percent) in &[("casper", 100, 200), ("marcel", 60, 150), ("lana", 40, 100)] {
+            let info = mock_info(&HumanAddr::from(*name), &coins(*amount, "uluna"));
+            let _ = execute(deps.as_mut(), mock_env(), info, bid(*percent)).unwrap();
         }
+        let qbids = query_bids(deps.as_ref(), None, None).unwrap();
+        assert_eq!(qbids.bids.len(), 3);
+        assert_eq!(qbids.bids[1].max_price, Decimal::percent(150));
+        assert!(qbids.bids.iter().all(|bid| bid.tokens.is_zero()));
+        assert_eq!(query_bids(deps.as_ref(), Some(1), None).unwrap().bids[0].bidder, HumanAddr::from("lana"));
+        assert_eq!(query_batch_clearing(deps.as_ref()).unwrap().clearing, None);
 
-        //fake callback
+        //casper alone doesn't sell out, marcel's bid does at 1.5 and gets what casper leaves
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CloseFundraise {}).unwrap();
         let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-        let msg = ExecuteMsg::_SendTokens {};
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-        //check if 150 token was minted
-        let qtoken = query_token_info(deps.as_ref()).unwrap();
-        assert_eq!(
-            qtoken,
-            TokenInfoResponse {
-                name: "Shark Coin".to_string(),
-                symbol: "ushark".to_string(),
-                decimals: 0,
-                total_supply: Uint128(150),
-            }
-        );
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::_SendTokens {}).unwrap();
+        assert_eq!(attr("minted", "100"), res.attributes[1]);
 
-        //check to see if contract was given minted coins
-        let qbal = query_balance(deps.as_ref(), HumanAddr::from(MOCK_CONTRACT_ADDR)).unwrap();
-        assert_eq!(
-            qbal,
-            BalanceResponse {
-                balance: Uint128(150)
-            }
-        );
+        let qclearing = query_batch_clearing(deps.as_ref()).unwrap();
+        assert_eq!(qclearing.bids, 3);
+        let clearing = qclearing.clearing.unwrap();
+        assert_eq!(clearing.price, Decimal::percent(150));
+        assert_eq!((clearing.marginal_supply, clearing.marginal_demand), (Uint128(34), Uint128(40)));
+        let tokens: Vec<Uint128> = query_bids(deps.as_ref(), None, None).unwrap().bids.iter().map(|bid| bid.tokens).collect();
+        assert_eq!(tokens, vec![Uint128(66), Uint128(34), Uint128(0)]);
+        let qfund = query_fundraise(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(qfund.clearing_price, Some(Decimal::percent(150)));
+        assert_eq!(qfund.proceeds_remaining, Uint128(150));
 
-        //check if 2 transfers are sent
-        assert_eq!(2, res.messages.len());
-        assert_eq!(attr("action", "transfer"), res.attributes[0]);
+        //everyone pays 1.5 for what they won, lana's losing bid comes back whole
+        for (name, tokens, refund) in &[("casper", "66", "1"), ("marcel", "34", "9")] {
+            let info = mock_info(&HumanAddr::from(*name), &[]);
+            let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::ClaimTokens {}).unwrap();
+            assert_eq!(attr("amount", *tokens), res.attributes[2]);
+            let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimRefund {}).unwrap();
+            assert_eq!(attr("amount", *refund), res.attributes[2]);
+        }
+        let info = mock_info(&HumanAddr::from("lana"), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::ClaimTokens {}).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimRefund {}).unwrap();
+        assert_eq!(attr("amount", "40"), res.attributes[2]);
 
-        //fake transfers
+        //bids sized in 18 decimals clear without overflowing the price math
+        let whole = 1_000_000_000_000_000_000u128;
+        let mut deps = mock_dependencies(&[]);
+        let instantiate_msg = InstantiateMsg {
+            batch_auction: Some(BatchAuction { reserve_price: Decimal::percent(50) }),
+            tokens_for_sale: Some(Uint128(1000 * whole)),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        for (name, amount, percent) in &[("casper", 1200 * whole, 200), ("marcel", 600 * whole, 150)] {
+            let info = mock_info(&HumanAddr::from(*name), &coins(*amount, "uluna"));
+            let _ = execute(deps.as_mut(), mock_env(), info, bid(*percent)).unwrap();
+        }
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CloseFundraise {}).unwrap();
         let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-        let msg = ExecuteMsg::Transfer {
-            amount: Uint128(100),
-            recipient: HumanAddr::from("casper"),
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::_SendTokens {}).unwrap();
+
+        let clearing = query_batch_clearing(deps.as_ref()).unwrap().clearing.unwrap();
+        assert_eq!(clearing.price, Decimal::percent(150));
+        assert_eq!(clearing.tokens_sold, Uint128(1000 * whole));
+        let tokens: Vec<Uint128> = query_bids(deps.as_ref(), None, None).unwrap().bids.iter().map(|bid| bid.tokens).collect();
+        assert_eq!(tokens, vec![Uint128(800 * whole), Uint128(200 * whole)]);
+        assert_eq!(query_fundraise(deps.as_ref(), mock_env()).unwrap().proceeds_remaining, Uint128(1500 * whole));
+        let info = mock_info(&HumanAddr::from("marcel"), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimRefund {}).unwrap();
+        assert_eq!(attr("amount", (300 * whole).to_string()), res.attributes[2]);
+
+        //bids only go to batch auctions
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+        let info = mock_info(&HumanAddr::from("casper"), &coins(100, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, bid(100)).unwrap_err();
+        assert_eq!(err, ContractError::NotBatchAuction {});
+        assert!(query_batch_clearing(deps.as_ref()).is_err());
+    }
+
+    #[test]
+    fn commit_reveal() {
+        let mut deps = mock_dependencies(&[]);
+        let mut env = mock_env();
+        let height = env.block.height;
+
+        //10 blocks to commit, 10 more to reveal, 10% kept from anyone who never reveals
+        let instantiate_msg = InstantiateMsg {
+            commit_reveal: Some(CommitReveal {
+                commit_end: height + 10,
+                reveal_end: height + 20,
+                penalty: Decimal::percent(10),
+            }),
+            ..mock_instantiate_msg()
         };
-        let _ = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), env.clone(), info, instantiate_msg).unwrap();
 
-        let msg = ExecuteMsg::Transfer {
-            amount: Uint128(50),
-            recipient: HumanAddr::from("marcel"),
+        let payload = |address: &str, amount: u128, salt: &str| CommitmentPayload {
+            address: HumanAddr::from(address),
+            amount: Uint128(amount),
+            max_price: None,
+            salt: Binary::from(salt.as_bytes()),
+        };
+        let commit = |address: &str, amount: u128, salt: &str| ExecuteMsg::Commit {
+            commitment: Binary::from(commitment_hash(&payload(address, amount, salt)).unwrap()),
+        };
+        let reveal = |amount: u128, salt: &str| ExecuteMsg::Reveal {
+            amount: Uint128(amount),
+            salt: Binary::from(salt.as_bytes()),
+            max_price: None,
+            allocation: None,
+            attestation: None,
         };
-        let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        //check their balance
-        let qbal = query_balance(deps.as_ref(), HumanAddr::from("casper")).unwrap();
-        assert_eq!(
-            qbal,
-            BalanceResponse {
-                balance: Uint128(100)
-            }
-        );
+        //contributions have to be committed first
+        let info = mock_info(&HumanAddr::from("casper"), &coins(100, "uluna"));
+        let err = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap_err();
+        assert_eq!(err, ContractError::CommitRequired {});
 
-        let qbal = query_balance(deps.as_ref(), HumanAddr::from("marcel")).unwrap();
-        assert_eq!(
-            qbal,
-            BalanceResponse {
-                balance: Uint128(50)
-            }
-        );
+        //only the fundraise denom can be escrowed
+        let info = mock_info(&HumanAddr::from("casper"), &[coin(150, "uluna"), coin(10, "uatom")]);
+        let err = execute(deps.as_mut(), env.clone(), info, commit("casper", 100, "casper")).unwrap_err();
+        assert_eq!(err, ContractError::InvalidFundraiseAsset {});
 
-    }
+        //casper escrows 150 to hide a 100 contribution
+        let info = mock_info(&HumanAddr::from("casper"), &coins(150, "uluna"));
+        let _ = execute(deps.as_mut(), env.clone(), info.clone(), commit("casper", 100, "casper")).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), info, commit("casper", 100, "casper")).unwrap_err();
+        assert_eq!(err, ContractError::CommitmentExists {});
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let err = execute(deps.as_mut(), env.clone(), info, reveal(100, "casper")).unwrap_err();
+        assert_eq!(err, ContractError::NotRevealPhase { start: height + 10, end: height + 20 });
+        for (name, escrow, amount) in &[("marcel", 50, 50), ("lana", 80, 80)] {
+            let info = mock_info(&HumanAddr::from(*name), &coins(*escrow, "uluna"));
+            let _ = execute(deps.as_mut(), env.clone(), info, commit(*name, *amount, *name)).unwrap();
+        }
 
-    #[test]
-    fn rate_sort() {
-        let mut rate_arr:Vec<Rate> = vec![];
+        //nothing counts towards the sale until it's revealed
+        let qfund = query_fundraise(deps.as_ref(), env.clone()).unwrap();
+        assert_eq!(qfund.commit_phase, Some(CommitPhase::Commit));
+        assert_eq!(qfund.escrowed, Uint128(280));
+        assert_eq!(qfund.fundraise_bal, Uint128(0));
 
-        let r1 = Rate {min: Uint128(5), ratio: Decimal::percent(120)};
-        let r2 = Rate {min: Uint128(10), ratio: Decimal::percent(200)};
+        env.block.height = height + 10;
+        let info = mock_info(&HumanAddr::from("cosmo"), &coins(10, "uluna"));
+        let err = execute(deps.as_mut(), env.clone(), info, commit("cosmo", 10, "cosmo")).unwrap_err();
+        assert_eq!(err, ContractError::CommitPhaseOver { height: height + 10 });
+        assert_eq!(query_fundraise(deps.as_ref(), env.clone()).unwrap().commit_phase, Some(CommitPhase::Reveal));
 
-        rate_arr.push(r1);
-        rate_arr.push(r2);
+        //reveals have to match, and whatever was escrowed on top comes back
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), reveal(100, "marcel")).unwrap_err();
+        assert_eq!(err, ContractError::InvalidReveal {});
+        let res = execute(deps.as_mut(), env.clone(), info, reveal(100, "casper")).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: HumanAddr::from("casper"),
+                amount: coins(50, "uluna"),
+            })]
+        );
+        let info = mock_info(&HumanAddr::from("marcel"), &[]);
+        let res = execute(deps.as_mut(), env.clone(), info, reveal(50, "marcel")).unwrap();
+        assert_eq!(0, res.messages.len());
+        let qfund = query_fundraise(deps.as_ref(), env.clone()).unwrap();
+        assert_eq!((qfund.fundraise_bal, qfund.escrowed), (Uint128(150), Uint128(80)));
 
-        assert_eq!(rate_arr[0], Rate {min: Uint128(5), ratio: Decimal::percent(120)});
+        //lana can't pull out while she could still reveal
+        let info = mock_info(&HumanAddr::from("lana"), &[]);
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::ReclaimCommitment {}).unwrap_err();
+        assert_eq!(err, ContractError::CommitmentLocked {});
 
-        rate_arr.sort_by(|a, b| b.min.cmp(&a.min));
+        //once the window is over she gets her escrow back less the penalty, which goes to the treasury
+        env.block.height = height + 20;
+        assert_eq!(query_fundraise(deps.as_ref(), env.clone()).unwrap().commit_phase, Some(CommitPhase::Ended));
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), reveal(80, "lana")).unwrap_err();
+        assert_eq!(err, ContractError::NotRevealPhase { start: height + 10, end: height + 20 });
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::ReclaimCommitment {}).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: HumanAddr::from("lana"),
+                    amount: coins(72, "uluna"),
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: HumanAddr::from("god"),
+                    amount: coins(8, "uluna"),
+                }),
+            ]
+        );
+        let err = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::ReclaimCommitment {}).unwrap_err();
+        assert_eq!(err, ContractError::NoCommitment {});
+        assert_eq!(query_fundraise(deps.as_ref(), env).unwrap().escrowed, Uint128(0));
 
-        assert_eq!(rate_arr[0], Rate {min: Uint128(10), ratio: Decimal::percent(200)});
+        //a copied commitment can't be revealed by the copier, and nobody reveals more than they escrowed
+        let mut deps = mock_dependencies(&[]);
+        let mut env = mock_env();
+        let instantiate_msg = InstantiateMsg {
+            commit_reveal: Some(CommitReveal {
+                commit_end: height + 10,
+                reveal_end: height + 20,
+                penalty: Decimal::percent(10),
+            }),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), env.clone(), info, instantiate_msg).unwrap();
+        let info = mock_info(&HumanAddr::from("cosmo"), &coins(150, "uluna"));
+        let _ = execute(deps.as_mut(), env.clone(), info, commit("casper", 100, "casper")).unwrap();
+        let info = mock_info(&HumanAddr::from("marcel"), &coins(60, "uluna"));
+        let _ = execute(deps.as_mut(), env.clone(), info, commit("marcel", 100, "marcel")).unwrap();
+        env.block.height = height + 10;
+        let info = mock_info(&HumanAddr::from("cosmo"), &[]);
+        let err = execute(deps.as_mut(), env.clone(), info, reveal(100, "casper")).unwrap_err();
+        assert_eq!(err, ContractError::InvalidReveal {});
+        let info = mock_info(&HumanAddr::from("marcel"), &[]);
+        let err = execute(deps.as_mut(), env.clone(), info, reveal(100, "marcel")).unwrap_err();
+        assert_eq!(err, ContractError::RevealAboveEscrow {});
     }
 
     #[test]
-    fn recieve_proper_rate() {
+    fn bonding_curve() {
         let mut deps = mock_dependencies(&[]);
 
-        // instantiate a contract
+        //starts at 1 uluna a token and rises 0.1 with every token sold, so s tokens need s + 0.05s^2 reserve
         let instantiate_msg = InstantiateMsg {
-            fundraise_goal: Uint128(10),
-            base_conv_ratio_num: Uint128(1),
-            base_conv_ratio_den: Uint128(1),
-            fundraise_denom: "uluna".to_string(),
-            name: "Shark Coin".to_string(),
-            symbol: "ushark".to_string(),
-            decimals: 0,
-            rates: Some(vec![RateInit {min: Uint128(10), ratio_num: Uint128(1), ratio_den: Uint128(2)}, 
-                             RateInit {min: Uint128(20), ratio_num: Uint128(1), ratio_den: Uint128(3)}]),
+            bonding_curve: Some(BondingCurve::Linear {
+                base_price: Decimal::one(),
+                slope: Decimal::percent(10),
+            }),
+            ..mock_instantiate_msg()
         };
-
         let info = mock_info(&HumanAddr::from("god"), &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
 
-        //add funds
-        let sender = HumanAddr::from("casper");
-        let balance = coins(5, "uluna");
-        let info = mock_info(&sender, &balance);
-        let msg = ExecuteMsg::AddFunds {};
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(attr("action", "add_funds"), res.attributes[0]);
+        let buy = |min_tokens: Option<u128>| ExecuteMsg::Buy {
+            min_tokens: min_tokens.map(Uint128),
+            allocation: None,
+            attestation: None,
+        };
+        let sell = |amount: u128, min_return: Option<u128>| ExecuteMsg::Sell {
+            amount: Uint128(amount),
+            min_return: min_return.map(Uint128),
+        };
 
-        //add funds
-        let sender = HumanAddr::from("marcel");
-        let balance = coins(10, "uluna");
-        let info = mock_info(&sender, &balance);
-        let msg = ExecuteMsg::AddFunds {};
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(attr("action", "add_funds"), res.attributes[0]);
+        //contributions have to be buys
+        let info = mock_info(&HumanAddr::from("casper"), &coins(100, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::AddFunds { allocation: None, attestation: None }).unwrap_err();
+        assert_eq!(err, ContractError::BuyRequired {});
 
-        //add funds
-        let sender = HumanAddr::from("kanye");
-        let balance = coins(50, "uluna");
-        let info = mock_info(&sender, &balance);
-        let msg = ExecuteMsg::AddFunds {};
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(attr("action", "add_funds"), res.attributes[0]);
+        //100 buys 35 tokens for 97, the 36th would take the reserve to 101
+        let qsim = query_simulate_buy(deps.as_ref(), Uint128(100)).unwrap();
+        assert_eq!((qsim.tokens, qsim.funds), (Uint128(35), Uint128(97)));
+        assert_eq!((qsim.price_before, qsim.price_after), (Decimal::one(), Decimal::percent(450)));
+        let res = execute(deps.as_mut(), mock_env(), info, buy(None)).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: HumanAddr::from("casper"),
+                amount: coins(3, "uluna"),
+            })]
+        );
+        assert_eq!(query_balance(deps.as_ref(), HumanAddr::from("casper")).unwrap().balance, Uint128(35));
 
-        //close fundraise
-        let sender = HumanAddr::from("casper");
-        let info = mock_info(&sender, &[]);
-        let msg = ExecuteMsg::CloseFundraise {};
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(1, res.messages.len());
-        assert_eq!(attr("action", "close_fundraise"), res.attributes[0]);
-        
-        let sendmsg = &res.messages[0];
-        match sendmsg {
-            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg:_, send }) => {
-                assert_eq!(send, &[]);
-                assert_eq!(contract_addr, &HumanAddr::from(MOCK_CONTRACT_ADDR));
-            }
-            _ => panic!("Unexpected message: {:?}", sendmsg),
-        }
+        //the price has moved, 50 more only buys 10
+        let info = mock_info(&HumanAddr::from("marcel"), &coins(50, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), buy(Some(20))).unwrap_err();
+        assert_eq!(err, ContractError::SlippageExceeded { amount: Uint128(10), min: Uint128(20) });
+        let res = execute(deps.as_mut(), mock_env(), info, buy(Some(10))).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(query_balance(deps.as_ref(), HumanAddr::from("marcel")).unwrap().balance, Uint128(10));
+        let qfund = query_fundraise(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!((qfund.curve_supply, qfund.fundraise_bal), (Uint128(45), Uint128(147)));
 
-        //fake callback
-        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-        let msg = ExecuteMsg::_SendTokens {};
-        let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        //at 5.5 a token 1 uluna buys nothing, and nobody can sell more than the curve has sold
+        let info = mock_info(&HumanAddr::from("lana"), &coins(1, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, buy(None)).unwrap_err();
+        assert_eq!(err, ContractError::BuyTooSmall {});
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, sell(46, None)).unwrap_err();
+        assert_eq!(err, ContractError::BeyondCurve {});
 
-        //check if 5 + 10*2 + 50*3 = 175 tokens were minted
-        let qtoken = query_token_info(deps.as_ref()).unwrap();
+        //selling 10 back walks the curve down to 35 and pays out the reserve in between
+        assert_eq!(query_simulate_sell(deps.as_ref(), Uint128(10)).unwrap().funds, Uint128(50));
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), sell(10, Some(60))).unwrap_err();
+        assert_eq!(err, ContractError::SlippageExceeded { amount: Uint128(50), min: Uint128(60) });
+        let res = execute(deps.as_mut(), mock_env(), info, sell(10, None)).unwrap();
         assert_eq!(
-            qtoken,
-            TokenInfoResponse {
-                name: "Shark Coin".to_string(),
-                symbol: "ushark".to_string(),
-                decimals: 0,
-                total_supply: Uint128(175),
-            }
+            res.messages,
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: HumanAddr::from("casper"),
+                amount: coins(50, "uluna"),
+            })]
         );
+        assert_eq!(query_balance(deps.as_ref(), HumanAddr::from("casper")).unwrap().balance, Uint128(25));
+        let info = mock_info(&HumanAddr::from("lana"), &[]);
+        assert!(execute(deps.as_mut(), mock_env(), info, sell(5, None)).is_err());
+
+        //closing stops sells and leaves the reserve as proceeds, with nothing more to mint or claim
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CloseFundraise {}).unwrap();
+        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::_SendTokens {}).unwrap();
+        assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, Uint128(35));
+        let info = mock_info(&HumanAddr::from("marcel"), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), sell(10, None)).unwrap_err();
+        assert_eq!(err, ContractError::FundraiseClosed {});
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimTokens {}).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyClaimed {});
+        let qfund = query_fundraise(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!((qfund.fundraise_bal, qfund.proceeds_remaining), (Uint128(97), Uint128(97)));
+
+        //selling back frees up room under the max contribution for buying again
+        let mut deps = mock_dependencies(&[]);
+        let instantiate_msg = InstantiateMsg {
+            bonding_curve: Some(BondingCurve::Linear {
+                base_price: Decimal::one(),
+                slope: Decimal::percent(10),
+            }),
+            max_contribution: Some(Uint128(100)),
+            ..mock_instantiate_msg()
+        };
+        let info = mock_info(&HumanAddr::from("god"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+        let info = mock_info(&HumanAddr::from("casper"), &coins(100, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, buy(None)).unwrap();
+        let info = mock_info(&HumanAddr::from("casper"), &[]);
+        let _ = execute(deps.as_mut(), mock_env(), info, sell(10, None)).unwrap();
+        assert_eq!(query_contribution(deps.as_ref(), HumanAddr::from("casper")).unwrap().balance, Uint128(57));
+        let info = mock_info(&HumanAddr::from("casper"), &coins(50, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, buy(None)).unwrap_err();
+        assert_eq!(err, ContractError::AboveMaxContribution { max: Uint128(100) });
+        let info = mock_info(&HumanAddr::from("casper"), &coins(40, "uluna"));
+        let _ = execute(deps.as_mut(), mock_env(), info, buy(None)).unwrap();
+        assert_eq!(query_balance(deps.as_ref(), HumanAddr::from("casper")).unwrap().balance, Uint128(35));
+        let qcontribution = query_contribution(deps.as_ref(), HumanAddr::from("casper")).unwrap();
+        assert_eq!((qcontribution.balance, qcontribution.deposits), (Uint128(97), coins(97, "uluna")));
     }
 }
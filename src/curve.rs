@@ -0,0 +1,185 @@
+//! bonding curves for continuous sales. everything is priced off reserve_at, the funds a curve holds
+//! once some supply has been bought from it, so a buy costs and a sell pays the difference between two
+//! supplies and the reserve always equals reserve_at(supply) however trades are split up
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Decimal, Uint128};
+
+use crate::state::{atomics, decimal, is_valid_price, mul_div, mul_div_ceil, DECIMAL_FRACTIONAL};
+
+/// the extra digits square roots are taken with
+const ROOT_FRACTIONAL: u128 = 1_000_000_000;
+
+/// how the price of a derivative token moves with the tokens a curve has sold
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BondingCurve {
+    /// price = base_price + slope * supply
+    Linear { base_price: Decimal, slope: Decimal },
+    /// prices like a constant product pool seeded with virtual_reserve funds and virtual_supply
+    /// tokens, rising without bound as supply gets close to virtual_supply
+    ConstantProduct {
+        virtual_reserve: Uint128,
+        virtual_supply: Uint128,
+    },
+    /// price = scale * sqrt(supply)
+    SquareRoot { scale: Decimal },
+}
+
+/// what a trade between two supplies looks like against the curve
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Quote {
+    /// spot price before the trade
+    pub price_before: Decimal,
+    /// spot price after the trade
+    pub price_after: Decimal,
+    /// funds per token the trade goes through at, None if no tokens trade
+    pub average_price: Option<Decimal>,
+    /// how far average_price is from price_before as a fraction of it, None if either is missing or 0
+    pub slippage: Option<Decimal>,
+}
+
+impl BondingCurve {
+    /// if the curve has a price above 0 and its parameters fit the atomics math
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            BondingCurve::Linear { base_price, slope } => {
+                let fits = |price: Decimal| price.is_zero() || is_valid_price(price);
+                (!base_price.is_zero() || !slope.is_zero()) && fits(base_price) && fits(slope)
+            }
+            BondingCurve::ConstantProduct {
+                virtual_reserve,
+                virtual_supply,
+            } => !virtual_reserve.is_zero() && !virtual_supply.is_zero(),
+            BondingCurve::SquareRoot { scale } => is_valid_price(scale),
+        }
+    }
+
+    /// funds backing supply tokens, rounded up. None past the last token the curve can sell or when
+    /// the math overflows
+    pub fn reserve_at(&self, supply: Uint128) -> Option<Uint128> {
+        let s = supply.u128();
+        let reserve = match *self {
+            BondingCurve::Linear { base_price, slope } => {
+                let base = mul_div_ceil(s, atomics(base_price), DECIMAL_FRACTIONAL)?;
+                let rise = mul_div_ceil(s.checked_mul(s)?, atomics(slope), 2 * DECIMAL_FRACTIONAL)?;
+                base.checked_add(rise)?
+            }
+            BondingCurve::ConstantProduct {
+                virtual_reserve,
+                virtual_supply,
+            } => {
+                let left = virtual_supply.u128().checked_sub(s).filter(|left| *left > 0)?;
+                mul_div_ceil(virtual_reserve.u128(), s, left)?
+            }
+            BondingCurve::SquareRoot { scale } => {
+                // 2/3 * scale * s^1.5, with the root carrying ROOT_FRACTIONAL
+                let root = isqrt(s.checked_mul(DECIMAL_FRACTIONAL)?);
+                mul_div_ceil(
+                    s.checked_mul(root)?,
+                    2 * atomics(scale),
+                    3 * DECIMAL_FRACTIONAL * ROOT_FRACTIONAL,
+                )?
+            }
+        };
+        Some(Uint128(reserve))
+    }
+
+    /// spot price of the next token at supply
+    pub fn price_at(&self, supply: Uint128) -> Option<Decimal> {
+        self.price_atomics(supply).map(decimal)
+    }
+
+    fn price_atomics(&self, supply: Uint128) -> Option<u128> {
+        let s = supply.u128();
+        let price = match *self {
+            BondingCurve::Linear { base_price, slope } => {
+                atomics(base_price).checked_add(s.checked_mul(atomics(slope))?)?
+            }
+            BondingCurve::ConstantProduct {
+                virtual_reserve,
+                virtual_supply,
+            } => {
+                let left = virtual_supply.u128().checked_sub(s).filter(|left| *left > 0)?;
+                let per_token = mul_div(virtual_reserve.u128(), DECIMAL_FRACTIONAL, left)?;
+                mul_div(per_token, virtual_supply.u128(), left)?
+            }
+            BondingCurve::SquareRoot { scale } => {
+                let root = isqrt(s.checked_mul(DECIMAL_FRACTIONAL)?);
+                mul_div(atomics(scale), root, ROOT_FRACTIONAL)?
+            }
+        };
+        Some(price)
+    }
+
+    /// most tokens funds buys at supply and what they cost, never more than funds
+    pub fn buy(&self, supply: Uint128, funds: Uint128) -> Option<(Uint128, Uint128)> {
+        let start = self.reserve_at(supply)?.u128();
+        let cost = |tokens: u128| {
+            let end = supply.u128().checked_add(tokens)?;
+            self.reserve_at(Uint128(end)).map(|reserve| reserve.u128() - start)
+        };
+        let affordable = |tokens: u128| cost(tokens).map_or(false, |cost| cost <= funds.u128());
+        // double until out of reach, then search the last doubling
+        let (mut low, mut high) = (0u128, 1u128);
+        while affordable(high) {
+            low = high;
+            high = high.checked_mul(2)?;
+        }
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if affordable(mid) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        Some((Uint128(low), Uint128(cost(low)?)))
+    }
+
+    /// funds the reserve pays out for tokens sold back at supply
+    pub fn sell(&self, supply: Uint128, tokens: Uint128) -> Option<Uint128> {
+        let after = supply.u128().checked_sub(tokens.u128())?;
+        let before = self.reserve_at(supply)?.u128();
+        Some(Uint128(before - self.reserve_at(Uint128(after))?.u128()))
+    }
+
+    /// prices either side of tokens trading for funds between supplies from and to
+    pub fn quote(&self, from: Uint128, to: Uint128, tokens: Uint128, funds: Uint128) -> Option<Quote> {
+        let before = self.price_atomics(from)?;
+        let average = if tokens.is_zero() {
+            None
+        } else {
+            Some(mul_div(funds.u128(), DECIMAL_FRACTIONAL, tokens.u128())?)
+        };
+        let slippage = match average {
+            Some(average) if before > 0 => {
+                let gap = if average > before { average - before } else { before - average };
+                Some(decimal(mul_div(gap, DECIMAL_FRACTIONAL, before)?))
+            }
+            _ => None,
+        };
+        Some(Quote {
+            price_before: decimal(before),
+            price_after: self.price_at(to)?,
+            average_price: average.map(decimal),
+            slippage,
+        })
+    }
+}
+
+/// floor of the square root of n
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = n / 2 + 1;
+    let mut y = (x + n / x) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
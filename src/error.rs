@@ -1,6 +1,8 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{HumanAddr, StdError, Uint128};
 use thiserror::Error;
 
+use crate::state::MAX_AUCTION_PRICE;
+
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
@@ -9,12 +11,243 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Fundraise goal must be greater than zero")]
+    ZeroFundraiseGoal {},
+
+    #[error("Sale can raise a native denom or a cw20 token, not both")]
+    AmbiguousFundraiseAsset {},
+
+    #[error("Fundraise denom cannot be empty")]
+    EmptyFundraiseDenom {},
+
+    #[error("Denom {denom} is accepted more than once")]
+    DuplicateDenom { denom: String },
+
+    #[error("Other denoms can only be accepted by native sales with an oracle to price them")]
+    MissingOracle {},
+
+    #[error("Base conversion ratio must have a non-zero numerator and denominator")]
+    InvalidBaseRatio {},
+
+    #[error("Rate with min {min} must have a non-zero numerator and denominator")]
+    InvalidRate { min: Uint128 },
+
+    #[error("More than one rate with min {min}")]
+    DuplicateRateMin { min: Uint128 },
+
+    #[error("Max contribution must be greater than zero and no less than the min contribution")]
+    InvalidContributionLimits {},
+
+    #[error("Merkle root must be a 32 byte sha256 hash")]
+    InvalidMerkleRoot {},
+
+    #[error("Attester public keys must be 33 or 65 byte secp256k1 keys")]
+    InvalidAttester {},
+
+    #[error("Hard cap cannot be below the fundraise goal")]
+    HardCapBelowGoal {},
+
+    #[error("Fundraise must end after it starts")]
+    InvalidSaleWindow {},
+
+    #[error("Max supply must be greater than zero")]
+    ZeroMaxSupply {},
+
+    #[error("Tokens for sale must be greater than zero")]
+    ZeroTokensForSale {},
+
+    #[error("Fixed supply sales sell every token at the base rate and cannot have rate tiers")]
+    RatesWithFixedSupply {},
+
+    #[error("Dutch auction must fall from a start price of at most {} to a non-zero floor over more than zero seconds", MAX_AUCTION_PRICE)]
+    InvalidDutchAuction {},
+
+    #[error("Dutch auctions set their own price and cannot have rate tiers")]
+    RatesWithAuction {},
+
+    #[error("Dutch auction prices fall over block time, so the sale has to end at a time rather than a height")]
+    AuctionEndsAtHeight {},
+
+    #[error("Batch auction reserve price must be greater than zero and at most {}", MAX_AUCTION_PRICE)]
+    InvalidBatchAuction {},
+
+    #[error("Sale can only run one kind of auction")]
+    MultipleAuctions {},
+
+    #[error("Auctions can only take the fundraise denom, not other accepted denoms")]
+    AcceptedDenomsWithAuction {},
+
+    #[error("Batch auctions need tokens_for_sale to clear against")]
+    MissingTokensForSale {},
+
+    #[error("Reveal window must end after the commit window and the penalty cannot exceed 100%")]
+    InvalidCommitReveal {},
+
+    #[error("Commit and reveal windows have to fall inside a sale window set in block heights")]
+    CommitRevealOutsideSale {},
+
+    #[error("Bonding curve needs a price above zero, with its base price, slope or scale at most {}", MAX_AUCTION_PRICE)]
+    InvalidBondingCurve {},
+
+    #[error("Bonding curves set the price and supply, so they cannot be combined with rates, tokens_for_sale, auctions, commit-reveal, a soft cap, vesting or accepted_denoms")]
+    BondingCurveConflict {},
+
+    #[error("Vesting must last longer than zero seconds and its cliff cannot outlast it")]
+    InvalidVestingSchedule {},
+
+    #[error("Reserve for {address} cannot be empty")]
+    EmptyReserve { address: HumanAddr },
+
+    #[error("Address {address} already has a reserve")]
+    DuplicateReserve { address: HumanAddr },
+
+    #[error("Percentage reserves must add up to less than the whole supply")]
+    ReservesExceedSupply {},
+
+    #[error("Fixed reserves add up to more than a Uint128 can hold")]
+    ReserveOverflow {},
+
+    #[error("Name is not in the expected format (3-50 UTF-8 bytes)")]
+    InvalidName {},
+
+    #[error("Ticker symbol is not in expected format [a-zA-Z\\-]{{3,12}}")]
+    InvalidSymbol {},
+
+    #[error("Decimals must not exceed 18")]
+    InvalidDecimals {},
+
     #[error("Must send a positive balance of accepted coins")]
     EmptyBalance {},
 
+    #[error("Sale does not accept this asset")]
+    InvalidFundraiseAsset {},
+
+    #[error("Sale is a batch auction, contribute with PlaceBid")]
+    BidRequired {},
+
+    #[error("Sale is not a batch auction")]
+    NotBatchAuction {},
+
+    #[error("Bid price must be at least the reserve price and at most {}", MAX_AUCTION_PRICE)]
+    InvalidBidPrice {},
+
+    #[error("Sale takes contributions through Commit and Reveal")]
+    CommitRequired {},
+
+    #[error("Sale does not use commit-reveal")]
+    NotCommitReveal {},
+
+    #[error("Commitments are only taken before block height {height}")]
+    CommitPhaseOver { height: u64 },
+
+    #[error("Reveals are only taken from block height {start} until {end}")]
+    NotRevealPhase { start: u64, end: u64 },
+
+    #[error("Commitment must be a 32 byte sha256 hash")]
+    InvalidCommitment {},
+
+    #[error("Sender already has a commitment waiting to be revealed")]
+    CommitmentExists {},
+
+    #[error("No commitment found for this account")]
+    NoCommitment {},
+
+    #[error("Revealed amount, price and salt do not match the commitment")]
+    InvalidReveal {},
+
+    #[error("Revealed amount is more than was escrowed")]
+    RevealAboveEscrow {},
+
+    #[error("Commitments can only be reclaimed once the reveal window ends or the sale closes")]
+    CommitmentLocked {},
+
+    #[error("Sale is a bonding curve, buy tokens with Buy")]
+    BuyRequired {},
+
+    #[error("Sale is not a bonding curve")]
+    NotBondingCurve {},
+
+    #[error("Amount is too small to buy a single token")]
+    BuyTooSmall {},
+
+    #[error("Trade would go past what the bonding curve can price")]
+    BeyondCurve {},
+
+    #[error("Trade returns {amount}, less than the {min} asked for")]
+    SlippageExceeded { amount: Uint128, min: Uint128 },
+
+    #[error("Contributions must add up to at least {min} per address")]
+    BelowMinContribution { min: Uint128 },
+
+    #[error("Contributions cannot add up to more than {max} per address")]
+    AboveMaxContribution { max: Uint128 },
+
+    #[error("Sender is not on the allowlist")]
+    NotAllowlisted {},
+
+    #[error("Sale is merkle allowlisted, send a cap and proof with the contribution")]
+    MissingAllocationProof {},
+
+    #[error("Cap and proof do not match the merkle root")]
+    InvalidAllocationProof {},
+
+    #[error("Sale requires KYC, send an attestation with the contribution")]
+    AttestationRequired {},
+
+    #[error("Attestation is not for the sender or not signed by a registered attester")]
+    InvalidAttestation {},
+
+    #[error("Attestation has expired")]
+    AttestationExpired {},
+
+    #[error("Attestation only covers contributions up to {max} in total")]
+    AboveAttestedAmount { max: Uint128 },
+
     #[error("Fundraising has been closed")]
     FundraiseClosed {},
 
+    #[error("Fundraising has not started yet")]
+    FundraiseNotStarted {},
+
+    #[error("Fundraising window has ended")]
+    FundraiseEnded {},
+
+    #[error("Refunds are only available once a soft cap sale has failed")]
+    RefundsUnavailable {},
+
+    #[error("No contribution found for this account")]
+    NoContribution {},
+
+    #[error("Contribution has already been refunded")]
+    AlreadyRefunded {},
+
+    #[error("Tokens can only be claimed after the sale closed successfully")]
+    ClaimsUnavailable {},
+
+    #[error("Tokens have already been claimed")]
+    AlreadyClaimed {},
+
+    #[error("No tokens were allocated to this account")]
+    NothingToClaim {},
+
+    #[error("Tokens vest for this sale, claim them with ClaimVested")]
+    TokensVesting {},
+
+    #[error("Tokens do not vest for this sale, claim them with ClaimTokens")]
+    NoVesting {},
+
+    #[error("No newly vested tokens to claim")]
+    NothingVested {},
+
+    #[error("Sender has no vesting reserve")]
+    NoReserve {},
+
+    #[error("Proceeds can only be withdrawn after the sale closed successfully")]
+    ProceedsUnavailable {},
+
+    #[error("Cannot withdraw more than the remaining proceeds")]
+    InsufficientProceeds {},
+
     #[error("Cannot set to own account")]
     CannotSetOwnAccount {},
 
@@ -1,4 +1,6 @@
 pub mod contract;
+pub mod curve;
 mod error;
+pub mod merkle;
 pub mod msg;
 pub mod state;
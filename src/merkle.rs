@@ -0,0 +1,85 @@
+//! sha256 merkle trees for allowlisted sales, shared by the contract and off-chain tooling.
+//! leaves are sha256(0x00 ++ address ++ cap) with the cap as 16 big endian bytes and parents are
+//! sha256(0x01 ++ children in sorted order), so a leaf can't pass for a node and proofs are just the
+//! list of siblings from leaf to root without saying which side each one is on
+
+use cosmwasm_std::{HumanAddr, Uint128};
+use sha2::{Digest, Sha256};
+
+pub type Hash = [u8; 32];
+
+/// first byte hashed into every leaf
+const LEAF_TAG: u8 = 0x00;
+/// first byte hashed into every parent
+const NODE_TAG: u8 = 0x01;
+
+/// leaf committing an address to the most it can contribute
+pub fn leaf_hash(address: &HumanAddr, cap: Uint128) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_TAG]);
+    hasher.update(address.as_str().as_bytes());
+    hasher.update(cap.u128().to_be_bytes());
+    hasher.finalize().into()
+}
+
+/// parent of two nodes, hashed smallest first
+pub fn node_hash(a: &Hash, b: &Hash) -> Hash {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_TAG]);
+    hasher.update(first);
+    hasher.update(second);
+    hasher.finalize().into()
+}
+
+/// if hashing leaf up through proof ends at root
+pub fn verify(root: &Hash, leaf: Hash, proof: &[Hash]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf, |node, sibling| node_hash(&node, sibling));
+    &computed == root
+}
+
+/// full tree kept layer by layer, leaves first. a node without a sibling moves up a layer unchanged
+pub struct MerkleTree {
+    layers: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    pub fn new(leaves: Vec<Hash>) -> Self {
+        let mut layers = vec![leaves];
+        while let Some(layer) = layers.last().filter(|layer| layer.len() > 1) {
+            let next = layer
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => node_hash(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+        MerkleTree { layers }
+    }
+
+    /// root to publish on chain, None for a tree without leaves
+    pub fn root(&self) -> Option<Hash> {
+        self.layers.last().and_then(|layer| layer.first()).copied()
+    }
+
+    /// siblings from the leaf at index up to the root, None if there is no such leaf
+    pub fn proof(&self, index: usize) -> Option<Vec<Hash>> {
+        if index >= self.layers[0].len() {
+            return None;
+        }
+        let mut proof = vec![];
+        let mut index = index;
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
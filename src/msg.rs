@@ -1,9 +1,16 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{HumanAddr, Uint128, Decimal};
+use cosmwasm_std::{Binary, Coin, HumanAddr, Uint128, Decimal};
+use cw20::Cw20ReceiveMsg;
+use cw0::Expiration;
+use std::cmp::Ordering;
 
-use crate::state::{Fundraiser, Rate};
+use crate::curve::BondingCurve;
+use crate::error::ContractError;
+use crate::state::{
+    AllowlistEntry, BatchAuction, BatchClearing, CommitReveal, Commitment, DutchAuction, FundraiseAsset, Fundraiser, Rate, Reserve, ReserveAmount, TierMode, VestingSchedule,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -13,10 +20,65 @@ pub struct InstantiateMsg {
     pub base_conv_ratio_num: Uint128,
     /// denominator of ratio of fundraise_denom:derivative_token (how much derivative_token made)
     pub base_conv_ratio_den: Uint128,
-    /// denom of coins sent to this contract for fundraising
+    /// denom of coins sent to this contract for fundraising, left empty for cw20 sales
+    #[serde(default)]
     pub fundraise_denom: String,
+    /// if set, the sale raises this cw20 token instead of native coins
+    pub fundraise_cw20: Option<HumanAddr>,
+    /// other native denoms to accept, valued in fundraise_denom at the oracle's price when sent
+    pub accepted_denoms: Option<Vec<String>>,
+    /// contract answering OracleQueryMsg::Price, needed for accepted_denoms
+    pub oracle: Option<HumanAddr>,
     /// nullable field of Rates
     pub rates: Option<Vec<RateInit>>,
+    /// whether a tier's ratio prices the whole balance that reaches it or only the part above its min,
+    /// whole if unset
+    #[serde(default)]
+    pub tier_mode: TierMode,
+    /// when contributions start being accepted, open immediately if unset
+    pub start: Option<Expiration>,
+    /// when contributions stop being accepted, never if unset
+    pub end: Option<Expiration>,
+    /// if set, a sale that closes below fundraise_goal mints nothing and refunds every contributor
+    #[serde(default)]
+    pub soft_cap: bool,
+    /// most fundraise_denom the sale will accept, reaching it closes the sale
+    pub hard_cap: Option<Uint128>,
+    /// where raised funds are withdrawn to, defaults to the instantiator
+    pub treasury: Option<HumanAddr>,
+    /// if set, purchased tokens unlock over block time after the sale closes
+    pub vesting: Option<VestingSchedule>,
+    /// team, advisor and treasury allocations minted alongside contributors' at close
+    pub reserves: Option<Vec<ReserveInit>>,
+    /// most derivative tokens that can ever exist, allocations are scaled down at close to fit
+    pub max_supply: Option<Uint128>,
+    /// if set, only this many derivative tokens are sold and an oversubscribed sale shares them out
+    /// pro-rata to each contribution
+    pub tokens_for_sale: Option<Uint128>,
+    /// least an address can have contributed in total, so its first contribution has to be at least this
+    pub min_contribution: Option<Uint128>,
+    /// most an address can contribute in total
+    pub max_contribution: Option<Uint128>,
+    /// if set, only addresses the owner adds to the allowlist can contribute
+    #[serde(default)]
+    pub allowlisted: bool,
+    /// if set, only addresses that prove they are in this sha256 merkle root can contribute
+    pub merkle_root: Option<Binary>,
+    /// if set, every contribution needs a KYC attestation signed by an attester the owner registered
+    #[serde(default)]
+    pub kyc_required: bool,
+    /// if set, tokens are sold in a Dutch auction at a falling price instead of the rates, and every buyer
+    /// settles at the clearing price when the sale closes
+    pub dutch_auction: Option<DutchAuction>,
+    /// if set, contributions are bids with a max price and tokens_for_sale is sold to the highest bids
+    /// at a single clearing price when the sale closes
+    pub batch_auction: Option<BatchAuction>,
+    /// if set, contributors commit a hash of their contribution with the funds escrowed and reveal it
+    /// later, so nobody can see what's coming before the commit phase ends
+    pub commit_reveal: Option<CommitReveal>,
+    /// if set, tokens are sold continuously along this curve, minted as they're bought and sellable back
+    /// to the reserve until the sale closes
+    pub bonding_curve: Option<BondingCurve>,
 
     /// name of the derivative token
     pub name: String,
@@ -26,6 +88,212 @@ pub struct InstantiateMsg {
     pub decimals: u8,
 }
 
+impl InstantiateMsg {
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if self.fundraise_goal.is_zero() {
+            return Err(ContractError::ZeroFundraiseGoal {});
+        }
+        match (&self.fundraise_cw20, self.fundraise_denom.is_empty()) {
+            (None, true) => return Err(ContractError::EmptyFundraiseDenom {}),
+            (Some(_), false) => return Err(ContractError::AmbiguousFundraiseAsset {}),
+            _ => {}
+        }
+        let mut denoms = vec![&self.fundraise_denom];
+        for denom in self.accepted_denoms.iter().flatten() {
+            if denom.is_empty() {
+                return Err(ContractError::EmptyFundraiseDenom {});
+            }
+            if denoms.contains(&denom) {
+                return Err(ContractError::DuplicateDenom { denom: denom.clone() });
+            }
+            denoms.push(denom);
+        }
+        if denoms.len() > 1 && (self.oracle.is_none() || self.fundraise_cw20.is_some()) {
+            return Err(ContractError::MissingOracle {});
+        }
+        // auction refunds hand contributors back different shares of what they paid, so withdrawals
+        // couldn't tell which denoms are still owed
+        if denoms.len() > 1 && (self.dutch_auction.is_some() || self.batch_auction.is_some()) {
+            return Err(ContractError::AcceptedDenomsWithAuction {});
+        }
+        if self.base_conv_ratio_num.is_zero() || self.base_conv_ratio_den.is_zero() {
+            return Err(ContractError::InvalidBaseRatio {});
+        }
+
+        // base rate always takes min 0, every other tier needs its own min
+        let mut mins = vec![Uint128(0)];
+        for rate in self.rates.iter().flatten() {
+            if rate.ratio_num.is_zero() || rate.ratio_den.is_zero() {
+                return Err(ContractError::InvalidRate { min: rate.min });
+            }
+            if mins.contains(&rate.min) {
+                return Err(ContractError::DuplicateRateMin { min: rate.min });
+            }
+            mins.push(rate.min);
+        }
+
+        if self.max_contribution.map_or(false, |max| max.is_zero()) {
+            return Err(ContractError::InvalidContributionLimits {});
+        }
+        if let (Some(min), Some(max)) = (self.min_contribution, self.max_contribution) {
+            if min > max {
+                return Err(ContractError::InvalidContributionLimits {});
+            }
+        }
+        if self.merkle_root.as_ref().map_or(false, |root| root.as_slice().len() != 32) {
+            return Err(ContractError::InvalidMerkleRoot {});
+        }
+        if let Some(hard_cap) = self.hard_cap {
+            if hard_cap < self.fundraise_goal {
+                return Err(ContractError::HardCapBelowGoal {});
+            }
+        }
+        if self.max_supply.map_or(false, |max| max.is_zero()) {
+            return Err(ContractError::ZeroMaxSupply {});
+        }
+        if let Some(tokens_for_sale) = self.tokens_for_sale {
+            if tokens_for_sale.is_zero() {
+                return Err(ContractError::ZeroTokensForSale {});
+            }
+            // pro-rata shares only follow contributions if everyone pays the same price
+            if self.rates.as_ref().map_or(false, |rates| !rates.is_empty()) {
+                return Err(ContractError::RatesWithFixedSupply {});
+            }
+        }
+        if let Some(auction) = &self.dutch_auction {
+            if !auction.is_valid() {
+                return Err(ContractError::InvalidDutchAuction {});
+            }
+            if self.rates.as_ref().map_or(false, |rates| !rates.is_empty()) {
+                return Err(ContractError::RatesWithAuction {});
+            }
+            // the price falls over block time, so only an end time says where it stopped
+            if matches!(self.end, Some(Expiration::AtHeight(_))) {
+                return Err(ContractError::AuctionEndsAtHeight {});
+            }
+        }
+        if let Some(auction) = &self.batch_auction {
+            if !auction.is_valid() {
+                return Err(ContractError::InvalidBatchAuction {});
+            }
+            if self.dutch_auction.is_some() {
+                return Err(ContractError::MultipleAuctions {});
+            }
+            // the clearing price is whatever sells tokens_for_sale, rate tiers are already ruled out by it
+            if self.tokens_for_sale.is_none() {
+                return Err(ContractError::MissingTokensForSale {});
+            }
+        }
+        if let Some(commit_reveal) = &self.commit_reveal {
+            if !commit_reveal.is_valid() {
+                return Err(ContractError::InvalidCommitReveal {});
+            }
+            // reveals are contributions, so the sale has to open before commits close and stay open
+            // through the reveals. a window in times can't be checked against heights
+            let opens_in_time = match self.start {
+                Some(Expiration::AtHeight(start)) => start < commit_reveal.commit_end,
+                Some(_) => false,
+                None => true,
+            };
+            let open_through_reveals = match self.end {
+                Some(Expiration::AtHeight(end)) => end >= commit_reveal.reveal_end,
+                Some(Expiration::Never {}) | None => true,
+                Some(_) => false,
+            };
+            if !opens_in_time || !open_through_reveals {
+                return Err(ContractError::CommitRevealOutsideSale {});
+            }
+        }
+        if let Some(curve) = &self.bonding_curve {
+            if !curve.is_valid() {
+                return Err(ContractError::InvalidBondingCurve {});
+            }
+            // the curve alone prices every token and it only holds fundraise_denom to pay sellers from
+            let conflicts = self.rates.as_ref().map_or(false, |rates| !rates.is_empty())
+                || self.accepted_denoms.as_ref().map_or(false, |denoms| !denoms.is_empty())
+                || self.tokens_for_sale.is_some()
+                || self.dutch_auction.is_some()
+                || self.batch_auction.is_some()
+                || self.commit_reveal.is_some()
+                || self.vesting.is_some()
+                || self.soft_cap;
+            if conflicts {
+                return Err(ContractError::BondingCurveConflict {});
+            }
+        }
+        if let Some(vesting) = &self.vesting {
+            if !vesting.is_valid() {
+                return Err(ContractError::InvalidVestingSchedule {});
+            }
+        }
+
+        // percentage reserves have to leave some of the supply for contributors
+        let mut addresses: Vec<&HumanAddr> = vec![];
+        let mut percent = Decimal::zero();
+        let mut fixed = Some(0u128);
+        for reserve in self.reserves.iter().flatten() {
+            let empty = match reserve.amount {
+                ReserveAmount::Percent(share) => {
+                    if share >= Decimal::one() {
+                        return Err(ContractError::ReservesExceedSupply {});
+                    }
+                    percent = percent + share;
+                    share.is_zero()
+                }
+                ReserveAmount::Fixed(amount) => {
+                    fixed = fixed.and_then(|total| total.checked_add(amount.u128()));
+                    amount.is_zero()
+                }
+            };
+            if empty {
+                return Err(ContractError::EmptyReserve { address: reserve.address.clone() });
+            }
+            if addresses.contains(&&reserve.address) {
+                return Err(ContractError::DuplicateReserve { address: reserve.address.clone() });
+            }
+            if reserve.vesting.map_or(false, |v| !v.is_valid()) {
+                return Err(ContractError::InvalidVestingSchedule {});
+            }
+            addresses.push(&reserve.address);
+        }
+        if percent >= Decimal::one() {
+            return Err(ContractError::ReservesExceedSupply {});
+        }
+        if fixed.is_none() {
+            return Err(ContractError::ReserveOverflow {});
+        }
+        if let Some(start) = &self.start {
+            // a height and a time can't be ordered, so a window mixing them is rejected too
+            if start.partial_cmp(&self.end.unwrap_or_default()) != Some(Ordering::Less) {
+                return Err(ContractError::InvalidSaleWindow {});
+            }
+        }
+
+        // same name, symbol and decimals rules as cw20-base
+        if !is_valid_name(&self.name) {
+            return Err(ContractError::InvalidName {});
+        }
+        if !is_valid_symbol(&self.symbol) {
+            return Err(ContractError::InvalidSymbol {});
+        }
+        if self.decimals > 18 {
+            return Err(ContractError::InvalidDecimals {});
+        }
+        Ok(())
+    }
+}
+
+/// 3-50 UTF-8 bytes, as cw20-base checks it (its own check is private)
+fn is_valid_name(name: &str) -> bool {
+    (3..=50).contains(&name.len())
+}
+
+/// 3-12 of [a-zA-Z\-], as cw20-base checks it (its own check is private)
+fn is_valid_symbol(symbol: &str) -> bool {
+    (3..=12).contains(&symbol.len())
+        && symbol.bytes().all(|byte| matches!(byte, b'-' | b'A'..=b'Z' | b'a'..=b'z'))
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct RateInit {
     /// min fundraise_denom sent to get this rate
@@ -37,43 +305,438 @@ pub struct RateInit {
     pub ratio_den: Uint128,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ReserveInit {
+    /// who the reserve is minted to
+    pub address: HumanAddr,
+    /// percentage of the total supply or a fixed number of derivative tokens
+    pub amount: ReserveAmount,
+    /// if set, the reserve unlocks over block time after the sale closes
+    pub vesting: Option<VestingSchedule>,
+}
+
+/// an address's cap in a merkle allowlisted sale, along with the proof it is in the tree
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AllocationProof {
+    /// most the address can contribute in total
+    pub cap: Uint128,
+    /// sibling hashes from the address's leaf up to the root
+    pub proof: Vec<Binary>,
+}
+
+/// a KYC provider's signed statement that address may contribute up to max_amount until it expires
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Attestation {
+    pub address: HumanAddr,
+    pub expires: Expiration,
+    /// most the address can contribute in total
+    pub max_amount: Uint128,
+    /// secp256k1 signature over the sha256 of this attestation's AttestationPayload
+    pub signature: Binary,
+}
+
+impl Attestation {
+    /// what the attester signed for the sale at contract
+    pub fn payload(&self, contract: HumanAddr) -> AttestationPayload {
+        AttestationPayload {
+            contract,
+            address: self.address.clone(),
+            expires: self.expires,
+            max_amount: self.max_amount,
+        }
+    }
+}
+
+/// what attesters sign, as the sha256 of its JSON. the sale's address stops an attestation being reused on other sales
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AttestationPayload {
+    pub contract: HumanAddr,
+    pub address: HumanAddr,
+    pub expires: Expiration,
+    pub max_amount: Uint128,
+}
+
+/// what a cw20 Send to the sale can carry as its msg, treated as AddFunds with nothing attached if unset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    AddFunds {
+        allocation: Option<AllocationProof>,
+        attestation: Option<Attestation>,
+    },
+    PlaceBid {
+        max_price: Decimal,
+        allocation: Option<AllocationProof>,
+        attestation: Option<Attestation>,
+    },
+    Commit {
+        commitment: Binary,
+    },
+    Buy {
+        min_tokens: Option<Uint128>,
+        allocation: Option<AllocationProof>,
+        attestation: Option<Attestation>,
+    },
+}
+
+/// what a commit-reveal contributor commits to, as the sha256 of its JSON. the contributor's address stops
+/// anyone else copying the commitment, max_price is only set for batch auction bids
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CommitmentPayload {
+    pub address: HumanAddr,
+    pub amount: Uint128,
+    pub max_price: Option<Decimal>,
+    pub salt: Binary,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     CloseFundraise {},
-    AddFunds {},
+    /// Contributes the fundraise_denom sent. Merkle allowlisted sales need the sender's cap and proof,
+    /// KYC sales an attestation for the sender
+    AddFunds {
+        allocation: Option<AllocationProof>,
+        attestation: Option<Attestation>,
+    },
+    /// Bids the fundraise_denom sent in a batch auction, paying at most max_price per derivative token.
+    /// Whatever the bid doesn't spend at the clearing price is refundable once the sale closes
+    PlaceBid {
+        max_price: Decimal,
+        allocation: Option<AllocationProof>,
+        attestation: Option<Attestation>,
+    },
+    /// Escrows the fundraise_denom sent against the sha256 of a CommitmentPayload, commit-reveal sales only.
+    /// One commitment per address at a time
+    Commit { commitment: Binary },
+    /// Contributes amount out of the sender's escrow if it matches their commitment, sending back the rest.
+    /// Batch auction bids reveal their max_price too
+    Reveal {
+        amount: Uint128,
+        salt: Binary,
+        max_price: Option<Decimal>,
+        allocation: Option<AllocationProof>,
+        attestation: Option<Attestation>,
+    },
+    /// Returns an unrevealed commitment's escrow once the reveal window ends or the sale closes.
+    /// Commitments left unrevealed for the whole window lose the sale's penalty to the treasury
+    ReclaimCommitment {},
+    /// Buys as many derivative tokens off the bonding curve as the fundraise_denom sent pays for, minting
+    /// them to the sender straight away and sending back what's left. Fails if that's under min_tokens
+    Buy {
+        min_tokens: Option<Uint128>,
+        allocation: Option<AllocationProof>,
+        attestation: Option<Attestation>,
+    },
+    /// Burns amount of the sender's derivative tokens and pays them what the bonding curve's reserve
+    /// gives for them, while the sale is open. Fails if that's under min_return
+    Sell {
+        amount: Uint128,
+        min_return: Option<Uint128>,
+    },
     _SendTokens{},
+    /// Returns the sender's contribution if the sale closed below its soft cap, or the part of it
+    /// left unused when the sale was oversubscribed or scaled down to fit the max supply
+    ClaimRefund {},
+    /// Moves the sender's derivative token allocation to them once the sale closed successfully
+    ClaimTokens {},
+    /// Moves the part of the sender's allocation that has vested so far to them, for sales with vesting
+    ClaimVested {},
+    /// Moves the part of the sender's vesting reserve that has unlocked so far to them
+    ClaimReserve {},
+    /// Owner only. Sends raised funds to the treasury once the sale closed successfully,
+    /// everything remaining if amount is unset
+    WithdrawProceeds { amount: Option<Uint128> },
+    /// Owner only. Changes where proceeds are withdrawn to
+    UpdateTreasury { treasury: HumanAddr },
+    /// Contributes cw20 tokens sent by the sale's fundraise_cw20 token on behalf of the original sender
+    Receive(Cw20ReceiveMsg),
+    /// Owner only. Registers and removes the secp256k1 public keys trusted to sign KYC attestations
+    UpdateAttesters {
+        add: Vec<Binary>,
+        remove: Vec<Binary>,
+    },
+    /// Owner only. Sets, rotates or clears the merkle root of allowed addresses
+    UpdateMerkleRoot { merkle_root: Option<Binary> },
+    /// Owner only. Adds or updates allowlist entries and removes addresses from it
+    UpdateAllowlist {
+        add: Vec<AllowlistEntry>,
+        remove: Vec<HumanAddr>,
+    },
     
     /// Implements CW20. Transfer is a base message to move tokens to another account without triggering actions
     Transfer {
         recipient: HumanAddr,
         amount: Uint128,
     },
+    /// Implements CW20. Burn is a base message to destroy tokens forever
+    Burn { amount: Uint128 },
+    /// Implements CW20. Send is a base message to transfer tokens to a contract and trigger an action
+    /// on the receiving contract.
+    Send {
+        contract: HumanAddr,
+        amount: Uint128,
+        msg: Option<Binary>,
+    },
+    /// Implements CW20 "approval" extension. Allows spender to access an additional amount tokens
+    /// from the owner's (env.sender) account. If expires is Some(), overwrites current allowance
+    /// expiration with this one.
+    IncreaseAllowance {
+        spender: HumanAddr,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Implements CW20 "approval" extension. Lowers the spender's access of tokens
+    /// from the owner's (env.sender) account by amount. If expires is Some(), overwrites current
+    /// allowance expiration with this one.
+    DecreaseAllowance {
+        spender: HumanAddr,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Implements CW20 "approval" extension. Transfers amount tokens from owner -> recipient
+    /// if `env.sender` has sufficient pre-approval.
+    TransferFrom {
+        owner: HumanAddr,
+        recipient: HumanAddr,
+        amount: Uint128,
+    },
+    /// Implements CW20 "approval" extension. Sends amount tokens from owner -> contract
+    /// if `env.sender` has sufficient pre-approval.
+    SendFrom {
+        owner: HumanAddr,
+        contract: HumanAddr,
+        amount: Uint128,
+        msg: Option<Binary>,
+    },
+    /// Implements CW20 "approval" extension. Destroys tokens forever
+    BurnFrom { owner: HumanAddr, amount: Uint128 },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     /// Shows how much we have raised so far and our goal
     FundraiseInfo {},
     
-    /// Shows how much has been staked for each address
-    StakedInfo {},
+    /// Shows how much has been staked for each address, ordered by address
+    StakedInfo {
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    },
+    /// Shows the addresses allowed to contribute and their caps, ordered by address
+    Allowlist {
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    },
+    /// Shows how much a single address has staked and the rate it currently qualifies for
+    Contribution { address: HumanAddr },
+
+    /// Shows what contributing amount from address would do right now, using the same rate lookup as distribution
+    SimulateContribution {
+        address: HumanAddr,
+        amount: Uint128,
+        allocation: Option<AllocationProof>,
+        attestation: Option<Attestation>,
+    },
+
+    /// Shows how many derivative tokens an address is owed and if they have been claimed
+    Claimable { address: HumanAddr },
+
+    /// Shows how much of an address's allocation has vested and been claimed
+    VestingStatus { address: HumanAddr },
+    /// Lists the team, advisor and treasury reserves and what each was minted
+    Reserves {},
+    /// Shows a Dutch auction's live price per derivative token, or its clearing price once closed
+    CurrentPrice {},
+    /// Lists batch auction bids in the order they were placed, with what each won once the auction clears
+    Bids {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Shows a batch auction's reserve price and how it cleared once closed
+    BatchClearing {},
+    /// Shows the commitment an address is waiting to reveal, if any
+    Commitment { address: HumanAddr },
+    /// Shows exactly what buying from the bonding curve with amount would mint right now and the price
+    /// it moves through, ignoring per address limits
+    SimulateBuy { amount: Uint128 },
+    /// Shows exactly what selling amount derivative tokens back to the bonding curve would pay right now
+    SimulateSell { amount: Uint128 },
 
     /// Implements CW20. Returns the current balance of the given address, 0 if unset.
     Balance { address: HumanAddr },
     /// Implements CW20. Returns metadata on the contract - name, decimals, supply, etc.
     TokenInfo {},
+    /// Implements CW20 "allowance" extension.
+    /// Returns how much spender can use from owner account, 0 if unset.
+    Allowance { owner: HumanAddr, spender: HumanAddr },
+    /// Implements CW20 "enumerable" extension (and "allowances")
+    /// Returns all allowances this owner has approved. Supports pagination.
+    AllAllowances {
+        owner: HumanAddr,
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    },
+    /// Implements CW20 "enumerable" extension
+    /// Returns all accounts that have balances. Supports pagination.
+    AllAccounts {
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    },
+}
+
+/// price query a multi-asset sale's oracle contract has to answer
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OracleQueryMsg {
+    /// how much of quote one unit of denom is worth, answered with a PriceResponse
+    Price { denom: String, quote: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PriceResponse {
+    /// units of quote per unit of denom
+    pub rate: Decimal,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct ListResponse {
     /// total staked
     pub total_staked: Uint128,
-    /// list all stakers and how much
+    /// stakers in this page and how much
     pub fundraisers: Vec<Fundraiser>,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ContributionResponse {
+    /// who contributed
+    pub address: HumanAddr,
+    /// total fundraise_denom contributed, 0 if they never contributed
+    pub balance: Uint128,
+    /// native coins behind balance per denom
+    pub deposits: Vec<Coin>,
+    /// rate tier the balance currently qualifies for, which only prices the part above its min in
+    /// marginal sales
+    pub rate: Rate,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SimulationResponse {
+    /// if the sale would take the contribution right now
+    pub accepted: bool,
+    /// why the contribution would be rejected
+    pub error: Option<String>,
+    /// fundraise_denom the sale would keep, anything above the hard cap is sent back
+    pub accepted_amount: Uint128,
+    /// rate tier the address's balance would qualify for afterwards
+    pub rate: Rate,
+    /// derivative tokens the contribution adds to the address's allocation
+    pub tokens: Uint128,
+    /// address's total derivative token allocation afterwards
+    pub total_tokens: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ClaimableResponse {
+    /// derivative tokens allocated to this address for its contribution
+    pub allocation: Uint128,
+    /// derivative tokens still waiting to be claimed
+    pub claimable: Uint128,
+    /// if the allocation has been claimed
+    pub claimed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CurrentPriceResponse {
+    /// fundraise_denom per derivative token
+    pub price: Decimal,
+    /// lowest the price can fall to
+    pub floor_price: Decimal,
+    /// if the sale has closed and price is what every buyer settles at
+    pub settled: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BidResponse {
+    pub id: u64,
+    pub bidder: HumanAddr,
+    /// fundraise_denom put up for the bid
+    pub amount: Uint128,
+    /// most the bidder will pay per derivative token
+    pub max_price: Decimal,
+    /// derivative tokens the bid won, 0 until the auction clears
+    pub tokens: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BidsResponse {
+    pub bids: Vec<BidResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BatchClearingResponse {
+    /// lowest max price a bid can name
+    pub reserve_price: Decimal,
+    /// derivative tokens the auction sells
+    pub tokens_for_sale: Uint128,
+    /// number of bids placed
+    pub bids: u64,
+    /// how the auction cleared, None until the sale closes
+    pub clearing: Option<BatchClearing>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CommitmentResponse {
+    pub commitment: Option<Commitment>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CurveSimulationResponse {
+    /// derivative tokens the trade mints or burns
+    pub tokens: Uint128,
+    /// fundraise_denom a buy costs or a sell pays out, a buy sends back whatever it doesn't spend
+    pub funds: Uint128,
+    /// spot price per derivative token before the trade
+    pub price_before: Decimal,
+    /// spot price per derivative token after the trade
+    pub price_after: Decimal,
+    /// funds per token the trade goes through at, None if no tokens trade
+    pub average_price: Option<Decimal>,
+    /// how far average_price is from price_before as a fraction of it, None if either is missing or 0
+    pub slippage: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct VestingStatusResponse {
+    /// derivative tokens allocated to this address
+    pub total: Uint128,
+    /// derivative tokens unlocked so far
+    pub vested: Uint128,
+    /// derivative tokens already claimed
+    pub claimed: Uint128,
+    /// block time the cliff passes at, or once tokens are releasing linearly the time the last of them
+    /// unlock. None before close or once everything has
+    pub next_unlock: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AllowlistResponse {
+    /// if the allowlist is enforced
+    pub allowlisted: bool,
+    pub entries: Vec<AllowlistEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ReservesResponse {
+    /// derivative tokens minted to reserves, 0 until the sale closes
+    pub total_reserved: Uint128,
+    pub reserves: Vec<Reserve>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct FundraiseInfoResponse {
     /// value of the goal we want to raise
@@ -86,8 +749,94 @@ pub struct FundraiseInfoResponse {
     pub owner: HumanAddr,
     /// If fundraising is open to contributions or not
     pub fundraising_open: bool,
-    /// Denom of token accepted to fundraise with
-    pub fundraise_denom: String,
+    /// native coin or cw20 token accepted to fundraise with
+    pub fundraise_asset: FundraiseAsset,
+    /// other native denoms accepted, priced by the oracle
+    pub accepted_denoms: Vec<String>,
+    /// contract pricing accepted_denoms
+    pub oracle: Option<HumanAddr>,
+    /// native coins raised per denom
+    pub deposits: Vec<Coin>,
     /// rates offered
     pub rates: Vec<Rate>,
+    /// if rates price the whole balance or each tranche of it
+    pub tier_mode: TierMode,
+    /// when contributions start being accepted
+    pub start: Option<Expiration>,
+    /// when contributions stop being accepted
+    pub end: Expiration,
+    /// if the sale refunds everyone when it closes below fundraise_goal
+    pub soft_cap: bool,
+    /// most fundraise_denom the sale will accept
+    pub hard_cap: Option<Uint128>,
+    /// where raised funds are withdrawn to
+    pub treasury: HumanAddr,
+    /// raised funds already withdrawn to the treasury
+    pub withdrawn: Uint128,
+    /// raised funds still held by the contract for withdrawal
+    pub proceeds_remaining: Uint128,
+    /// how purchased tokens unlock after close
+    pub vesting: Option<VestingSchedule>,
+    /// block time the sale was closed at
+    pub closed_at: Option<u64>,
+    /// most derivative tokens that can ever exist
+    pub max_supply: Option<Uint128>,
+    /// fixed number of derivative tokens on offer
+    pub tokens_for_sale: Option<Uint128>,
+    /// least an address can have contributed in total
+    pub min_contribution: Option<Uint128>,
+    /// most an address can contribute in total
+    pub max_contribution: Option<Uint128>,
+    /// if only allowlisted addresses can contribute
+    pub allowlisted: bool,
+    /// merkle root of allowed addresses, if the sale uses one
+    pub merkle_root: Option<Binary>,
+    /// if contributions need a KYC attestation
+    pub kyc_required: bool,
+    /// public keys trusted to sign KYC attestations
+    pub attesters: Vec<Binary>,
+    /// Dutch auction pricing the sale, if any
+    pub dutch_auction: Option<DutchAuction>,
+    /// batch auction selling the tokens, if any
+    pub batch_auction: Option<BatchAuction>,
+    /// block heights of the commit and reveal phases, if the sale uses them
+    pub commit_reveal: Option<CommitReveal>,
+    /// which commit-reveal phase the sale is in
+    pub commit_phase: Option<CommitPhase>,
+    /// funds held against unrevealed commitments
+    pub escrowed: Uint128,
+    /// bonding curve pricing the sale, if any
+    pub bonding_curve: Option<BondingCurve>,
+    /// derivative tokens bought from the bonding curve and not sold back
+    pub curve_supply: Uint128,
+    /// price every auction buyer settles at, set once the auction closes
+    pub clearing_price: Option<Decimal>,
+    /// where the sale currently is in its window
+    pub status: SaleStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SaleStatus {
+    /// start has not been reached yet
+    Pending,
+    /// accepting contributions
+    Open,
+    /// end has passed but nobody has closed the sale yet
+    Ended,
+    /// sale has been closed
+    Closed,
+    /// sale closed below its soft cap, contributions are refundable
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitPhase {
+    /// taking commitments
+    Commit,
+    /// taking reveals of earlier commitments
+    Reveal,
+    /// reveal window is over, unrevealed commitments can be reclaimed less the penalty
+    Ended,
 }
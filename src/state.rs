@@ -1,8 +1,16 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{CanonicalAddr, HumanAddr, Decimal, Uint128};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Binary, CanonicalAddr, HumanAddr, Decimal, Uint128};
+use cw_storage_plus::{Item, Map, U64Key};
+use cw0::{Expiration, NativeBalance};
+
+use crate::curve::BondingCurve;
+
+/// Decimal's fixed point scale, Decimal::one() is this many atomics
+pub(crate) const DECIMAL_FRACTIONAL: u128 = 1_000_000_000_000_000_000;
+/// highest auction price whose atomics can still be scaled by DECIMAL_FRACTIONAL without overflowing
+pub const MAX_AUCTION_PRICE: u128 = u128::MAX / DECIMAL_FRACTIONAL / DECIMAL_FRACTIONAL;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct ICOInfo {
@@ -10,25 +18,218 @@ pub struct ICOInfo {
     
     /// value of the goal we want to raise
     pub fundraise_goal: Uint128,
-    ///value of how much we have raised so far
-    pub fundraise_bal: Uint128,
     ///initial ratio of fundraise_denom:ASSET 
     pub base_conv_ratio: Decimal,
     /// who created this ICO
     pub owner: CanonicalAddr,
     /// If fundraising is open to contributions or not
     pub fundraising_open: bool,
-    /// Denom of token accepted to fundraise with
-    pub fundraise_denom: String,
-    ///list of contributors and how much they have sent
-    pub fundraisers: Vec<Fundraiser>,
+    /// native coin or cw20 token accepted to fundraise with
+    pub fundraise_asset: FundraiseAsset,
+    /// other native denoms accepted alongside the fundraise denom, priced into it by the oracle
+    pub accepted_denoms: Vec<String>,
+    /// contract answering OracleQueryMsg::Price for accepted_denoms
+    pub oracle: Option<HumanAddr>,
     /// list of alternate rates for different sent amounts
     pub rates: Vec<Rate>,
+    /// if a tier's ratio prices the whole balance that reaches it or only the part above its min
+    pub tier_mode: TierMode,
+    /// when contributions start being accepted, None if open from instantiation
+    pub start: Option<Expiration>,
+    /// when contributions stop being accepted
+    pub end: Expiration,
+    /// if a sale closing below fundraise_goal is refunded instead of distributed
+    pub soft_cap: bool,
+    /// most fundraise_denom the sale will accept before closing itself
+    pub hard_cap: Option<Uint128>,
+    /// where raised funds are withdrawn to
+    pub treasury: CanonicalAddr,
+    /// raised funds already withdrawn to the treasury
+    pub withdrawn: Uint128,
+    /// if set, purchased tokens unlock over time after the sale closes instead of all at once
+    pub vesting: Option<VestingSchedule>,
+    /// block time the sale was closed at, vesting starts from here
+    pub closed_at: Option<u64>,
+    /// fixed number of derivative tokens on offer, oversubscribed sales share them out pro-rata
+    pub tokens_for_sale: Option<Uint128>,
+    /// least an address can have contributed in total once it contributes
+    pub min_contribution: Option<Uint128>,
+    /// most an address can contribute in total
+    pub max_contribution: Option<Uint128>,
+    /// if only addresses on the allowlist can contribute
+    pub allowlisted: bool,
+    /// sha256 merkle root of every allowed address and its cap, replaces the stored allowlist when set
+    pub merkle_root: Option<Binary>,
+    /// if every contribution needs a KYC attestation signed by one of the attesters
+    pub kyc_required: bool,
+    /// secp256k1 public keys of the KYC providers trusted to sign attestations
+    pub attesters: Vec<Binary>,
+    /// if set, tokens are priced by a falling Dutch auction instead of the rates
+    pub dutch_auction: Option<DutchAuction>,
+    /// if set, contributions are bids and tokens_for_sale clears at one price when the sale closes
+    pub batch_auction: Option<BatchAuction>,
+    /// how the batch auction cleared, None until the sale closes
+    pub batch_clearing: Option<BatchClearing>,
+    /// if set, contributions are committed as hashes first and only count once revealed
+    pub commit_reveal: Option<CommitReveal>,
+    /// if set, tokens are minted along this curve as they're bought and can be sold back until close
+    pub bonding_curve: Option<BondingCurve>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum FundraiseAsset {
+    /// native coins sent along with AddFunds
+    Native { denom: String },
+    /// cw20 tokens sent to the sale through the token's Send
+    Cw20 { contract_addr: HumanAddr },
+}
+
+impl ICOInfo {
+    /// a closed soft cap sale that never reached its goal
+    pub fn is_failed(&self, totals: &Totals) -> bool {
+        !self.fundraising_open && self.soft_cap && totals.fundraise_bal < self.fundraise_goal
+    }
+
+    /// best rate tier a total contribution of balance qualifies for
+    pub fn rate_for(&self, balance: Uint128) -> Rate {
+        match self.rates.iter().find(|r| balance >= r.min) {
+            Some(r) => r.clone(),
+            None => Rate {
+                min: Uint128(0),
+                ratio: self.base_conv_ratio,
+            },
+        }
+    }
+
+    /// derivative tokens owed for a total contribution of balance, the one place rate tiers are priced for
+    /// contributions, simulations and migration alike
+    pub fn allocation_for(&self, balance: Uint128) -> Uint128 {
+        match self.tier_mode {
+            TierMode::Whole => balance * self.rate_for(balance).ratio,
+            //rates run from the highest min down, each tier pricing what lies between its min and the one above
+            TierMode::Marginal => {
+                let mut upper = balance;
+                let mut tokens = Uint128(0);
+                for rate in self.rates.iter().filter(|r| r.min < balance) {
+                    tokens += Uint128(upper.u128() - rate.min.u128()) * rate.ratio;
+                    upper = rate.min;
+                }
+                tokens
+            }
+        }
+    }
+
+    /// allocation of a fundraiser with balance and allocation once they add amount at block time.
+    /// Dutch auctions add what amount buys at the live price up to the tokens they have left, batch
+    /// auction bids are only allocated once the sale clears, bonding curve buys are priced against the
+    /// curve's supply instead, and everything else reprices the whole balance
+    pub fn allocation_after(
+        &self,
+        totals: &Totals,
+        balance: Uint128,
+        allocation: Uint128,
+        amount: Uint128,
+        time: u64,
+    ) -> Uint128 {
+        match (&self.dutch_auction, &self.batch_auction, &self.bonding_curve) {
+            (Some(auction), _, _) => {
+                let tokens = tokens_for(amount, auction.price_at(time));
+                allocation + self.auction_tokens_left(totals).map_or(tokens, |left| tokens.min(left))
+            }
+            (None, None, None) => self.allocation_for(balance + amount),
+            _ => allocation,
+        }
+    }
+
+    /// derivative tokens a Dutch auction still has on offer, None if it isn't one or has no tokens_for_sale
+    pub fn auction_tokens_left(&self, totals: &Totals) -> Option<Uint128> {
+        match (&self.dutch_auction, self.tokens_for_sale) {
+            (Some(_), Some(supply)) => Some(Uint128(supply.u128().saturating_sub(totals.allocated.u128()))),
+            _ => None,
+        }
+    }
+
+    /// price every auction buyer settles at, None before close or if the sale isn't an auction. a Dutch
+    /// auction closed after its end settles at the last price anyone could buy at
+    pub fn clearing_price(&self) -> Option<Decimal> {
+        match (&self.dutch_auction, self.closed_at) {
+            (Some(auction), Some(closed_at)) => Some(auction.price_at(match self.end {
+                Expiration::AtTime(end) => closed_at.min(end),
+                _ => closed_at,
+            })),
+            _ => self.batch_clearing.as_ref().map(|clearing| clearing.price),
+        }
+    }
 
+    /// part of balance that paid for allocation, auctions refund whatever buyers bid above the clearing price
+    pub fn spent(&self, balance: Uint128, allocation: Uint128) -> Uint128 {
+        match self.clearing_price() {
+            Some(price) => balance.min(cost_of(allocation, price)),
+            None => balance,
+        }
+    }
+
+    /// raised funds the owner can still withdraw, contributions left unused at close stay behind for refunds.
+    /// floors at zero should rounding ever put what's gone and held back above what was spent
+    pub fn proceeds_remaining(&self, totals: &Totals) -> Uint128 {
+        let spent = self.spent(totals.fundraise_bal, totals.allocated);
+        let unused = totals.unused_of(spent);
+        Uint128(spent.u128().saturating_sub(unused.u128()).saturating_sub(self.withdrawn.u128()))
+    }
 }
 
 pub const ICO: Item<ICOInfo> = Item::new("ico");
 
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+pub struct Totals {
+    ///value of how much we have raised so far
+    pub fundraise_bal: Uint128,
+    /// number of addresses that have contributed
+    pub fundraisers: u64,
+    /// derivative tokens owed across all fundraisers
+    pub allocated: Uint128,
+    /// derivative tokens minted for fundraisers at close, below allocated if the sale was oversubscribed
+    /// or scaled down to fit the max supply
+    pub distributed: Option<Uint128>,
+    /// native coins kept from contributions per denom, empty for cw20 sales
+    pub deposits: NativeBalance,
+    /// batch auction bids placed so far, also the id of the next one
+    pub bids: u64,
+    /// funds held against commitments that haven't been revealed or reclaimed yet
+    pub escrowed: Uint128,
+    /// derivative tokens bought from the bonding curve and not sold back
+    pub curve_supply: Uint128,
+}
+
+impl Totals {
+    /// part of an allocation actually handed out, scaled down by however much the sale was cut at close.
+    /// rounds down, so shares never add up to more than was distributed
+    pub fn share_of(&self, allocation: Uint128) -> Uint128 {
+        match self.distributed {
+            Some(distributed) if distributed < self.allocated => {
+                allocation.multiply_ratio(distributed, self.allocated)
+            }
+            _ => allocation,
+        }
+    }
+
+    /// part of a contribution that bought nothing because allocations were scaled down at close
+    pub fn unused_of(&self, balance: Uint128) -> Uint128 {
+        match self.distributed {
+            Some(distributed) if distributed < self.allocated => {
+                balance.multiply_ratio(self.allocated.u128() - distributed.u128(), self.allocated)
+            }
+            _ => Uint128(0),
+        }
+    }
+}
+
+pub const TOTALS: Item<Totals> = Item::new("totals");
+
+/// contributors keyed by canonical address
+pub const FUNDRAISERS: Map<&[u8], Fundraiser> = Map::new("fundraisers");
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Fundraiser {
     /// who sent Luna
@@ -36,6 +237,376 @@ pub struct Fundraiser {
 
     /// Balance of Native tokens sent to ICO
     pub balance: Uint128,
+
+    /// Native tokens already paid back to this fundraiser
+    pub refunded: Uint128,
+
+    /// derivative tokens owed to this fundraiser, before any scaling at close
+    pub allocation: Uint128,
+
+    /// derivative tokens this fundraiser has already claimed
+    pub claimed: Uint128,
+
+    /// native coins kept from this fundraiser per denom, balance is what they were worth when sent
+    pub deposits: NativeBalance,
+}
+
+/// addresses allowed to contribute to allowlisted sales, keyed by canonical address
+pub const ALLOWLIST: Map<&[u8], AllowlistEntry> = Map::new("allowlist");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AllowlistEntry {
+    /// who may contribute
+    pub address: HumanAddr,
+    /// most this address can contribute in total, on top of the sale wide max
+    pub cap: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+pub struct VestingSchedule {
+    /// seconds after vesting starts before anything unlocks
+    pub cliff: u64,
+    /// seconds after vesting starts until everything is unlocked
+    pub duration: u64,
+}
+
+impl VestingSchedule {
+    /// a schedule has to take some time and cannot lock anything past its own end
+    pub fn is_valid(&self) -> bool {
+        self.duration > 0 && self.cliff <= self.duration
+    }
+
+    /// how much of total has unlocked at time, releasing linearly from start once the cliff passes
+    pub fn vested(&self, total: Uint128, start: u64, time: u64) -> Uint128 {
+        let elapsed = time.saturating_sub(start);
+        if elapsed < self.cliff {
+            Uint128(0)
+        } else if elapsed >= self.duration {
+            total
+        } else {
+            total.multiply_ratio(elapsed, self.duration)
+        }
+    }
+
+    /// when the cliff passes, or once tokens are releasing linearly when the last of them unlock. None once
+    /// everything has
+    pub fn next_unlock(&self, start: u64, time: u64) -> Option<u64> {
+        let elapsed = time.saturating_sub(start);
+        if elapsed < self.cliff {
+            Some(start + self.cliff)
+        } else if elapsed < self.duration {
+            Some(start + self.duration)
+        } else {
+            None
+        }
+    }
+}
+
+/// price per derivative token, falling linearly from start_price to floor_price over duration seconds
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+pub struct DutchAuction {
+    /// fundraise_denom per derivative token when the auction starts
+    pub start_price: Decimal,
+    /// lowest the price falls to
+    pub floor_price: Decimal,
+    /// block time the price starts falling from
+    pub start_time: u64,
+    /// seconds the price takes to reach the floor
+    pub duration: u64,
+}
+
+impl DutchAuction {
+    /// the price has to fall to a non-zero floor, and take some time doing it
+    pub fn is_valid(&self) -> bool {
+        is_valid_price(self.floor_price)
+            && is_valid_price(self.start_price)
+            && self.floor_price <= self.start_price
+            && self.duration > 0
+    }
+
+    /// live price at block time
+    pub fn price_at(&self, time: u64) -> Decimal {
+        let elapsed = time.saturating_sub(self.start_time).min(self.duration);
+        let start = atomics(self.start_price);
+        let fall = start - atomics(self.floor_price);
+        //elapsed never passes duration, so the drop always fits
+        let drop = mul_div(fall, elapsed as u128, self.duration as u128).unwrap_or(fall);
+        let price = start - drop;
+        Decimal::from_ratio(price, DECIMAL_FRACTIONAL)
+    }
+}
+
+/// uniform price auction, bids name the most they'll pay and every winning bid pays the same clearing price
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+pub struct BatchAuction {
+    /// lowest max price a bid can name, and the clearing price if bids don't cover tokens_for_sale
+    pub reserve_price: Decimal,
+}
+
+impl BatchAuction {
+    pub fn is_valid(&self) -> bool {
+        is_valid_price(self.reserve_price)
+    }
+}
+
+/// batch auction bids keyed by id, in the order they were placed
+pub const BIDS: Map<U64Key, Bid> = Map::new("bids");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Bid {
+    pub id: u64,
+    /// who placed the bid
+    pub bidder: HumanAddr,
+    /// fundraise_denom put up for the bid
+    pub amount: Uint128,
+    /// most the bidder will pay per derivative token
+    pub max_price: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BatchClearing {
+    /// price every winning bid pays per derivative token
+    pub price: Decimal,
+    /// tokens left for bids at exactly the clearing price, shared pro-rata between them
+    pub marginal_supply: Uint128,
+    /// tokens the bids at exactly the clearing price asked for, 0 if they all filled in full
+    pub marginal_demand: Uint128,
+    /// derivative tokens won across every bid
+    pub tokens_sold: Uint128,
+}
+
+impl BatchClearing {
+    /// derivative tokens a bid won, bids under the clearing price win nothing. rounds down, so fills never
+    /// add up to more than was sold
+    pub fn fill(&self, bid: &Bid) -> Uint128 {
+        if bid.max_price < self.price {
+            return Uint128(0);
+        }
+        let tokens = tokens_for(bid.amount, self.price);
+        if bid.max_price == self.price && !self.marginal_demand.is_zero() {
+            tokens.multiply_ratio(self.marginal_supply, self.marginal_demand)
+        } else {
+            tokens
+        }
+    }
+}
+
+/// clears supply against bids at the highest price that sells all of it, or the lowest price every bid
+/// can pay if they never ask for that much. bids at exactly a clearing price they'd oversell share what's left
+pub fn clear_batch(bids: &[Bid], supply: Uint128, reserve_price: Decimal) -> BatchClearing {
+    let mut sorted: Vec<&Bid> = bids.iter().collect();
+    sorted.sort_by(|a, b| b.max_price.cmp(&a.max_price));
+
+    //walk down the bid prices, above is everything bid higher than the current one
+    let mut above = 0u128;
+    let mut clearing = None;
+    let mut i = 0;
+    while i < sorted.len() {
+        let level = sorted[i].max_price;
+        let at_level: Vec<&Bid> = sorted[i..].iter().take_while(|b| b.max_price == level).copied().collect();
+        let at_level_amount: u128 = at_level.iter().map(|b| b.amount.u128()).sum();
+
+        //bids above already sell out at a price over this one, this level and below lose
+        let price = price_for(above, supply);
+        if price > level {
+            clearing = Some((price, false));
+            break;
+        }
+        //this level tips demand over the supply, it clears here and shares what the bids above leave
+        if tokens_for(Uint128(above + at_level_amount), level) >= supply {
+            clearing = Some((level, true));
+            break;
+        }
+        above += at_level_amount;
+        i += at_level.len();
+    }
+    let (price, marginal) =
+        clearing.unwrap_or_else(|| (reserve_price.max(price_for(above, supply)), false));
+
+    let mut full = 0u128;
+    let mut demand = 0u128;
+    for bid in bids {
+        let tokens = tokens_for(bid.amount, price).u128();
+        if bid.max_price > price || (bid.max_price == price && !marginal) {
+            full += tokens;
+        } else if bid.max_price == price {
+            demand += tokens;
+        }
+    }
+    let mut clearing = BatchClearing {
+        price,
+        marginal_supply: Uint128(supply.u128().saturating_sub(full).min(demand)),
+        marginal_demand: Uint128(demand),
+        tokens_sold: Uint128(0),
+    };
+    clearing.tokens_sold = bids
+        .iter()
+        .fold(Uint128(0), |total, bid| total + clearing.fill(bid));
+    clearing
+}
+
+/// lowest price amount can pay for all of tokens at, rounded up. saturates at the largest Decimal rather
+/// than overflowing
+fn price_for(amount: u128, tokens: Uint128) -> Decimal {
+    decimal(mul_div_ceil(amount, DECIMAL_FRACTIONAL, tokens.u128()).unwrap_or(u128::MAX))
+}
+
+/// auction prices have to be non-zero and small enough for the atomics math
+pub fn is_valid_price(price: Decimal) -> bool {
+    !price.is_zero() && price <= Decimal::from_ratio(MAX_AUCTION_PRICE, 1u128)
+}
+
+/// derivative tokens amount buys at price, rounded down
+pub fn tokens_for(amount: Uint128, price: Decimal) -> Uint128 {
+    amount.multiply_ratio(DECIMAL_FRACTIONAL, atomics(price))
+}
+
+/// what tokens cost at price, rounded up so buyers never pay less than their tokens are worth. saturates
+/// at the largest Uint128 rather than overflowing
+pub fn cost_of(tokens: Uint128, price: Decimal) -> Uint128 {
+    Uint128(mul_div_ceil(tokens.u128(), atomics(price), DECIMAL_FRACTIONAL).unwrap_or(u128::MAX))
+}
+
+/// Decimal as its underlying atomics
+pub(crate) fn atomics(decimal: Decimal) -> u128 {
+    (Uint128(DECIMAL_FRACTIONAL) * decimal).u128()
+}
+
+/// Decimal from atomics, built in two parts so from_ratio never scales past u128
+pub(crate) fn decimal(atomics: u128) -> Decimal {
+    Decimal::from_ratio(atomics / DECIMAL_FRACTIONAL, 1u128)
+        + Decimal::from_ratio(atomics % DECIMAL_FRACTIONAL, DECIMAL_FRACTIONAL)
+}
+
+/// a * b / c rounded down, None if the result doesn't fit a u128
+pub(crate) fn mul_div(a: u128, b: u128, c: u128) -> Option<u128> {
+    div_wide(a, b, c).map(|(quotient, _)| quotient)
+}
+
+/// a * b / c rounded up
+pub(crate) fn mul_div_ceil(a: u128, b: u128, c: u128) -> Option<u128> {
+    let (quotient, remainder) = div_wide(a, b, c)?;
+    match remainder {
+        0 => Some(quotient),
+        _ => quotient.checked_add(1),
+    }
+}
+
+/// quotient and remainder of a * b / c through the full 256 bit product
+fn div_wide(a: u128, b: u128, c: u128) -> Option<(u128, u128)> {
+    let (high, low) = full_mul(a, b);
+    if c == 0 || high >= c {
+        return None;
+    }
+    let (mut remainder, mut quotient) = (high, 0u128);
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    Some((quotient, remainder))
+}
+
+/// a * b as high and low halves
+fn full_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & MASK);
+    let (b_high, b_low) = (b >> 64, b & MASK);
+    let low_low = a_low * b_low;
+    let high_low = a_high * b_low;
+    let low_high = a_low * b_high;
+    let high_high = a_high * b_high;
+    let middle = (low_low >> 64) + (high_low & MASK) + (low_high & MASK);
+    let low = (low_low & MASK) | (middle << 64);
+    let high = high_high + (high_low >> 64) + (low_high >> 64) + (middle >> 64);
+    (high, low)
+}
+
+/// block heights a commit-reveal sale moves between its phases at
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+pub struct CommitReveal {
+    /// block height commitments are taken until, reveals start here
+    pub commit_end: u64,
+    /// block height reveals are taken until
+    pub reveal_end: u64,
+    /// share of a commitment left unrevealed past reveal_end that is sent to the treasury when reclaimed
+    pub penalty: Decimal,
+}
+
+impl CommitReveal {
+    /// reveals need a window of their own, and the penalty can't take more than was escrowed
+    pub fn is_valid(&self) -> bool {
+        self.commit_end < self.reveal_end && self.penalty <= Decimal::one()
+    }
+}
+
+/// commitments waiting to be revealed, keyed by canonical address
+pub const COMMITMENTS: Map<&[u8], Commitment> = Map::new("commitments");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Commitment {
+    /// who committed
+    pub owner: HumanAddr,
+    /// sha256 of the CommitmentPayload the owner will reveal
+    pub hash: Binary,
+    /// fundraise_denom held until the reveal, the revealed amount can be anything up to this
+    pub escrow: Uint128,
+}
+
+/// team, advisor and treasury allocations minted at close, keyed by canonical address
+pub const RESERVES: Map<&[u8], Reserve> = Map::new("reserves");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Reserve {
+    /// who the reserve is minted to
+    pub address: HumanAddr,
+    /// size of the reserve
+    pub amount: ReserveAmount,
+    /// if set, the reserve is held by the contract and unlocks over time after close
+    pub vesting: Option<VestingSchedule>,
+    /// derivative tokens minted for this reserve, 0 until the sale closes
+    pub allocation: Uint128,
+    /// derivative tokens this reserve has already received
+    pub claimed: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ReserveAmount {
+    /// share of the total supply minted at close, contributors and reserves included
+    Percent(Decimal),
+    /// fixed number of derivative tokens
+    Fixed(Uint128),
+}
+
+/// size of each reserve once contributors have been allocated contributed tokens, in the same order.
+/// saturates at the largest Uint128 rather than overflowing, minting past the cap fails on its own
+pub fn reserve_allocations(reserves: &[Reserve], contributed: Uint128) -> Vec<Uint128> {
+    let mut fixed = 0u128;
+    let mut percent = Decimal::zero();
+    for reserve in reserves {
+        match reserve.amount {
+            ReserveAmount::Fixed(amount) => fixed = fixed.saturating_add(amount.u128()),
+            ReserveAmount::Percent(share) => percent = percent + share,
+        }
+    }
+
+    //everything that isn't a percentage reserve makes up the rest of the supply
+    let rest = contributed.u128().saturating_add(fixed);
+    let rest_share = DECIMAL_FRACTIONAL - atomics(percent);
+    let supply = Uint128(mul_div(rest, DECIMAL_FRACTIONAL, rest_share).unwrap_or(u128::MAX));
+
+    reserves
+        .iter()
+        .map(|reserve| match reserve.amount {
+            ReserveAmount::Fixed(amount) => amount,
+            ReserveAmount::Percent(share) => supply * share,
+        })
+        .collect()
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -46,3 +617,40 @@ pub struct Rate {
     /// conversion rate from fundraise_denom:ASSET
     pub ratio: Decimal,
 }
+
+/// how rate tiers price a fundraiser's total contribution
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TierMode {
+    /// the whole balance gets the ratio of the highest tier it reaches
+    Whole,
+    /// each tranche of the balance gets its own tier's ratio, like tax brackets, each rounded down
+    Marginal,
+}
+
+impl Default for TierMode {
+    fn default() -> Self {
+        TierMode::Whole
+    }
+}
+
+/// ICOInfo as stored before contributors moved out into FUNDRAISERS, only read by migrate
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct LegacyICOInfo {
+    pub fundraise_goal: Uint128,
+    pub fundraise_bal: Uint128,
+    pub base_conv_ratio: Decimal,
+    pub owner: CanonicalAddr,
+    pub fundraising_open: bool,
+    pub fundraise_denom: String,
+    pub fundraisers: Vec<LegacyFundraiser>,
+    pub rates: Vec<Rate>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct LegacyFundraiser {
+    pub source: HumanAddr,
+    pub balance: Uint128,
+}
+
+pub const LEGACY_ICO: Item<LegacyICOInfo> = Item::new("ico");